@@ -3,7 +3,7 @@ use fyrox::{event_loop::EventLoop, resource::model::Model, scene::sound::SoundBu
 use fyroxed_base::{Editor, StartupData};
 use station_iapetus::{
     bot::BotKind,
-    character::{Character, HitBox},
+    character::{Character, HitBox, Limb},
     door::{DoorDirection, DoorState},
     elevator::call_button::CallButtonKind,
     inventory::{Inventory, ItemEntry},
@@ -14,8 +14,12 @@ use station_iapetus::{
         turret::{Barrel, Hostility, ShootMode},
     },
     player::camera::CameraController,
+    status_effect::StatusEffectKind,
     utils::ResourceProxy,
-    weapon::{definition::WeaponKind, projectile::Damage},
+    weapon::{
+        definition::WeaponKind,
+        projectile::{Damage, DamageType},
+    },
     GameConstructor,
 };
 
@@ -39,7 +43,10 @@ fn main() {
     editors.register_inheritable_enum::<BotKind, _>();
     editors.register_inheritable_enum::<CallButtonKind, _>();
     editors.register_inheritable_enum::<Damage, _>();
+    editors.register_inheritable_enum::<DamageType, _>();
     editors.register_inheritable_enum::<TriggerKind, _>();
+    editors.register_inheritable_enum::<StatusEffectKind, _>();
+    editors.register_inheritable_enum::<Limb, _>();
     editors.register_inheritable_inspectable::<Inventory>();
     editors.register_inheritable_inspectable::<ItemEntry>();
     editors.register_inheritable_inspectable::<Barrel>();
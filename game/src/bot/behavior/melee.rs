@@ -1,12 +1,12 @@
 use crate::character::DamageDealer;
 use crate::{
-    bot::{behavior::BehaviorContext, upper_body::UpperBodyMachine, BotDefinition},
+    bot::{behavior::BehaviorContext, upper_body::UpperBodyMachine, BotDefinition, LimbState},
     character::{CharacterMessage, CharacterMessageData},
-    utils,
+    utils::{self, is_probability_event_occurred},
 };
 use fyrox::{
     asset::core::rand::prelude::IteratorRandom,
-    core::{rand::Rng, visitor::prelude::*},
+    core::visitor::prelude::*,
     utils::behavior::{Behavior, Status},
 };
 
@@ -27,6 +27,12 @@ fn can_shoot(upper_body_machine: &UpperBodyMachine, definition: &BotDefinition)
         && definition.can_use_weapons
 }
 
+/// Checks whether a bot still has a limb to perform an attack with.
+fn is_attack_available(definition: &BotDefinition, limbs: &[LimbState], index: usize) -> bool {
+    let limb = definition.attack_animations[index].limb;
+    !limbs.iter().any(|l| l.severed && l.limb == limb)
+}
+
 impl<'a> Behavior<'a> for DoMeleeAttack {
     type Context = BehaviorContext<'a>;
 
@@ -44,15 +50,23 @@ impl<'a> Behavior<'a> for DoMeleeAttack {
         let attack_animation = animations_container.get_mut(current_attack_animation);
         let attack_animation_ended = attack_animation.has_ended();
 
-        if self.attack_timeout <= 0.0 && (attack_animation_ended || !attack_animation.is_enabled())
+        if self.attack_timeout <= 0.0
+            && (attack_animation_ended || !attack_animation.is_enabled())
+            && context
+                .character
+                .try_spend_stamina(context.definition.stamina.melee_cost)
         {
             // HACK: setting this to false messes up animation, so set speed to 0.0.
             attack_animation.set_enabled(true).set_speed(0.0).rewind();
             attack_animation.events_mut().clear();
 
-            self.attack_animation_index = fyrox::core::rand::thread_rng()
-                .gen_range(0..context.upper_body_machine.attack_animations.len())
-                as u32;
+            // Attacks that use severed limbs are not possible anymore.
+            if let Some(index) = (0..context.upper_body_machine.attack_animations.len())
+                .filter(|&i| is_attack_available(context.definition, context.limbs, i))
+                .choose(&mut fyrox::core::rand::thread_rng())
+            {
+                self.attack_animation_index = index as u32;
+            }
 
             animations_container
                 .get_mut(
@@ -91,7 +105,16 @@ impl<'a> Behavior<'a> for DoMeleeAttack {
                 if event.signal_id == UpperBodyMachine::HIT_SIGNAL
                     && active_state == context.upper_body_machine.attack_state
                     && !can_shoot(context.upper_body_machine, context.definition)
+                    && is_attack_available(
+                        context.definition,
+                        context.limbs,
+                        self.attack_animation_index as usize,
+                    )
                 {
+                    let attack_damage = context.definition.attack_animations
+                        [self.attack_animation_index as usize]
+                        .damage;
+
                     context.script_message_sender.send_global(CharacterMessage {
                         character: target.handle,
                         data: CharacterMessageData::Damage {
@@ -100,15 +123,22 @@ impl<'a> Behavior<'a> for DoMeleeAttack {
                             },
                             hitbox: None,
                             /// TODO: Find hit box maybe?
-                            amount: context.definition.attack_animations
-                                [self.attack_animation_index as usize]
-                                .damage
-                                .amount(),
+                            amount: attack_damage.amount(),
+                            kind: attack_damage.kind(),
                             critical_hit_probability: 0.0,
                             position: None,
                         },
                     });
 
+                    for &(effect, probability) in context.definition.attack_effects.iter() {
+                        if is_probability_event_occurred(probability) {
+                            context.script_message_sender.send_global(CharacterMessage {
+                                character: target.handle,
+                                data: CharacterMessageData::ApplyStatusEffect(effect),
+                            });
+                        }
+                    }
+
                     if let Some(attack_sound) = context
                         .definition
                         .attack_sounds
@@ -143,7 +173,10 @@ impl<'a> Behavior<'a> for CanMeleeAttack {
         match context.target {
             None => Status::Failure,
             Some(_) => {
-                if context.restoration_time <= 0.0 {
+                let has_any_attack = (0..context.definition.attack_animations.len())
+                    .any(|i| is_attack_available(context.definition, context.limbs, i));
+
+                if context.restoration_time <= 0.0 && has_any_attack {
                     Status::Success
                 } else {
                     Status::Failure
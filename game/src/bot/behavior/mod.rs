@@ -12,7 +12,7 @@ use crate::{
         },
         lower_body::LowerBodyMachine,
         upper_body::UpperBodyMachine,
-        BotDefinition, BotKind, Target,
+        BotDefinition, BotKind, LimbState, Target,
     },
     character::Character,
     utils::BodyImpactHandler,
@@ -108,6 +108,7 @@ pub struct BehaviorContext<'a> {
     pub animation_player: Handle<Node>,
     pub script_message_sender: &'a ScriptMessageSender,
     pub navmesh: Handle<Node>,
+    pub limbs: &'a [LimbState],
 
     // Output
     pub attack_animation_index: usize,
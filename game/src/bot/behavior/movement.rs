@@ -1,6 +1,6 @@
 use crate::{
     bot::{behavior::BehaviorContext, lower_body::LowerBodyMachine, upper_body::UpperBodyMachine},
-    character::HitBox,
+    character::Character,
     utils,
     utils::BodyImpactHandler,
 };
@@ -18,19 +18,24 @@ pub struct MoveToTarget {
 
 fn calculate_movement_speed_factor(
     upper_body_machine: &UpperBodyMachine,
-    hit_boxes: &[HitBox],
+    character: &Character,
     impact_handler: &BodyImpactHandler,
     scene: &Scene,
     animation_player: Handle<Node>,
 ) -> f32 {
-    let mut k = if upper_body_machine.should_stick_to_target(scene, animation_player) {
+    let mut k = if character.can_run()
+        && upper_body_machine.should_stick_to_target(scene, animation_player)
+    {
         2.0
     } else {
         1.0
     };
 
+    // Status effects slow the bot down too.
+    k *= character.status_effects.movement_factor();
+
     // Slowdown bot according to damaged body parts.
-    for hitbox in hit_boxes.iter() {
+    for hitbox in character.hit_boxes.iter() {
         let body = scene.graph[hitbox.collider].parent();
         if impact_handler.is_affected(body) {
             k = hitbox.movement_speed_factor.min(k);
@@ -46,7 +51,7 @@ impl<'a> Behavior<'a> for MoveToTarget {
     fn tick(&mut self, ctx: &mut Self::Context) -> Status {
         ctx.movement_speed_factor = calculate_movement_speed_factor(
             ctx.upper_body_machine,
-            &ctx.character.hit_boxes,
+            ctx.character,
             ctx.impact_handler,
             ctx.scene,
             ctx.animation_player,
@@ -1,7 +1,7 @@
 use crate::{
     bot::behavior::BehaviorContext,
     level::item::ItemKind,
-    weapon::{weapon_ref, WeaponMessage, WeaponMessageData},
+    weapon::{weapon_mut, weapon_ref, WeaponMessage, WeaponMessageData},
 };
 use fyrox::{
     core::visitor::prelude::*,
@@ -24,18 +24,25 @@ impl<'a> Behavior<'a> for ShootTarget {
 
             context.is_aiming_weapon = true;
 
-            let weapon = weapon_ref(weapon_handle, &context.scene.graph);
-            if weapon.can_shoot(context.elapsed_time) {
-                let ammo_per_shot = *weapon.ammo_consumption_per_shot;
+            let weapon = weapon_mut(weapon_handle, &mut context.scene.graph);
+            weapon.update_spread(
+                context.is_moving,
+                context.movement_speed_factor > 1.0,
+                true,
+                context.dt,
+            );
+            let trigger = weapon.auto_trigger_state();
+            if weapon.update_trigger(trigger, context.elapsed_time, context.dt) {
+                if weapon_mut(weapon_handle, &mut context.scene.graph).try_consume_ammo() {
+                    let weapon = weapon_ref(weapon_handle, &context.scene.graph);
 
-                if context
-                    .character
-                    .inventory
-                    .try_extract_exact_items(ItemKind::Ammo, ammo_per_shot)
-                    == ammo_per_shot
-                {
-                    context.v_recoil.set_target(weapon.gen_v_recoil_angle());
-                    context.h_recoil.set_target(weapon.gen_h_recoil_angle());
+                    let recoil_factor = context.character.status_effects.recoil_factor();
+                    context
+                        .v_recoil
+                        .set_target(weapon.gen_v_recoil_angle() * recoil_factor);
+                    context
+                        .h_recoil
+                        .set_target(weapon.gen_h_recoil_angle() * recoil_factor);
 
                     context.script_message_sender.send_to_target(
                         weapon_handle,
@@ -48,6 +55,9 @@ impl<'a> Behavior<'a> for ShootTarget {
                     );
 
                     return Status::Success;
+                } else if context.character.try_begin_reload(&mut context.scene.graph) {
+                    // Keep aiming while reloading.
+                    return Status::Running;
                 } else {
                     // Fallback to melee.
                     return Status::Failure;
@@ -72,7 +82,6 @@ impl<'a> Behavior<'a> for CanShootTarget {
         {
             let weapon_handle = *weapon;
             let weapon = weapon_ref(weapon_handle, &context.scene.graph);
-            let ammo_per_shot = *weapon.ammo_consumption_per_shot;
 
             if context.restoration_time <= 0.0
                 && context.definition.can_use_weapons
@@ -82,7 +91,9 @@ impl<'a> Behavior<'a> for CanShootTarget {
                     .items()
                     .iter()
                     .any(|i| i.kind.associated_weapon().is_some())
-                && context.character.inventory.item_count(ItemKind::Ammo) >= ammo_per_shot
+                && (weapon.has_ammo_for_shot()
+                    || weapon.is_reloading()
+                    || context.character.inventory.item_count(ItemKind::Ammo) > 0)
             {
                 Status::Success
             } else {
@@ -21,6 +21,10 @@ pub struct LowerBodyMachine {
     pub dying_animation: Handle<Animation>,
     pub scream_animation: Handle<Animation>,
     pub walk_state: Handle<State>,
+    #[visit(optional)]
+    pub crawl_animation: Handle<Animation>,
+    #[visit(optional)]
+    pub crawl_state: Handle<State>,
 }
 
 #[derive(Debug)]
@@ -28,6 +32,8 @@ pub struct LowerBodyMachineInput {
     pub walk: bool,
     pub scream: bool,
     pub dead: bool,
+    /// Set when a bot has lost a leg, it cannot walk anymore and crawls instead.
+    pub crawl: bool,
     pub movement_speed_factor: f32,
 }
 
@@ -42,6 +48,10 @@ impl LowerBodyMachine {
     const SCREAM_TO_IDLE: &'static str = "ScreamToIdle";
     const WALK_TO_DYING: &'static str = "WalkToDying";
     const IDLE_TO_DYING: &'static str = "IdleToDying";
+    const IDLE_TO_CRAWL: &'static str = "IdleToCrawl";
+    const WALK_TO_CRAWL: &'static str = "WalkToCrawl";
+    const SCREAM_TO_CRAWL: &'static str = "ScreamToCrawl";
+    const CRAWL_TO_DYING: &'static str = "CrawlToDying";
 
     pub async fn new(
         resource_manager: ResourceManager,
@@ -56,11 +66,19 @@ impl LowerBodyMachine {
             walk_animation_resource,
             scream_animation_resource,
             dying_animation_resource,
+            crawl_animation_resource,
         ) = fyrox::core::futures::join!(
             resource_manager.request_model(&definition.idle_animation,),
             resource_manager.request_model(&definition.walk_animation,),
             resource_manager.request_model(&definition.scream_animation,),
             resource_manager.request_model(&definition.dying_animation,),
+            // Walk animation is used for crawling if there's no specific animation for it.
+            resource_manager.request_model(
+                definition
+                    .crawl_animation
+                    .as_ref()
+                    .unwrap_or(&definition.walk_animation),
+            ),
         );
 
         let mut machine = Machine::new();
@@ -103,6 +121,15 @@ impl LowerBodyMachine {
             animation_player,
         );
 
+        let (crawl_animation, crawl_state) = create_play_animation_state(
+            crawl_animation_resource.unwrap(),
+            "Crawl",
+            root_layer,
+            scene,
+            model,
+            animation_player,
+        );
+
         let animations_container =
             utils::fetch_animation_container_mut(&mut scene.graph, animation_player);
 
@@ -142,6 +169,17 @@ impl LowerBodyMachine {
                 enabled: true,
             });
 
+        let crawl_animation_ref = &mut animations_container[crawl_animation];
+
+        crawl_animation_ref.set_speed(definition.crawl_animation_speed);
+        crawl_animation_ref.set_root_motion_settings(Some(RootMotionSettings {
+            node: hips,
+            ignore_x_movement: false,
+            ignore_y_movement: true,
+            ignore_z_movement: false,
+            ignore_rotations: true,
+        }));
+
         root_layer.add_transition(Transition::new(
             "Idle->Walk",
             idle_state,
@@ -199,6 +237,35 @@ impl LowerBodyMachine {
             Self::IDLE_TO_DYING,
         ));
 
+        root_layer.add_transition(Transition::new(
+            "Idle->Crawl",
+            idle_state,
+            crawl_state,
+            0.3,
+            Self::IDLE_TO_CRAWL,
+        ));
+        root_layer.add_transition(Transition::new(
+            "Walk->Crawl",
+            walk_state,
+            crawl_state,
+            0.3,
+            Self::WALK_TO_CRAWL,
+        ));
+        root_layer.add_transition(Transition::new(
+            "Scream->Crawl",
+            scream_state,
+            crawl_state,
+            0.3,
+            Self::SCREAM_TO_CRAWL,
+        ));
+        root_layer.add_transition(Transition::new(
+            "Crawl->Dying",
+            crawl_state,
+            dying_state,
+            0.2,
+            Self::CRAWL_TO_DYING,
+        ));
+
         root_layer.set_entry_state(idle_state);
 
         Self {
@@ -207,6 +274,8 @@ impl LowerBodyMachine {
             walk_animation,
             dying_animation,
             walk_state,
+            crawl_animation,
+            crawl_state,
         }
     }
 
@@ -217,18 +286,38 @@ impl LowerBodyMachine {
         input: LowerBodyMachineInput,
         animation_player: Handle<Node>,
     ) {
+        // There's no separate state for a bot that lies still, so just freeze the crawling.
+        if input.crawl {
+            utils::fetch_animation_container_mut(&mut scene.graph, animation_player)
+                .get_mut(self.crawl_animation)
+                .set_enabled(input.walk);
+        }
+
         let animations_container =
             utils::fetch_animation_container_ref(&scene.graph, animation_player);
 
         self.machine
-            .set_parameter(Self::IDLE_TO_WALK, Parameter::Rule(input.walk))
+            .set_parameter(
+                Self::IDLE_TO_WALK,
+                Parameter::Rule(input.walk && !input.crawl),
+            )
             .set_parameter(Self::WALK_TO_IDLE, Parameter::Rule(!input.walk))
-            .set_parameter(Self::IDLE_TO_SCREAM, Parameter::Rule(input.scream))
-            .set_parameter(Self::WALK_TO_SCREAM, Parameter::Rule(input.scream))
+            .set_parameter(
+                Self::IDLE_TO_SCREAM,
+                Parameter::Rule(input.scream && !input.crawl),
+            )
+            .set_parameter(
+                Self::WALK_TO_SCREAM,
+                Parameter::Rule(input.scream && !input.crawl),
+            )
             .set_parameter(Self::SCREAM_TO_WALK, Parameter::Rule(!input.scream))
             .set_parameter(Self::SCREAM_TO_IDLE, Parameter::Rule(!input.scream))
             .set_parameter(Self::WALK_TO_DYING, Parameter::Rule(input.dead))
             .set_parameter(Self::IDLE_TO_DYING, Parameter::Rule(input.dead))
+            .set_parameter(Self::IDLE_TO_CRAWL, Parameter::Rule(input.crawl))
+            .set_parameter(Self::WALK_TO_CRAWL, Parameter::Rule(input.crawl))
+            .set_parameter(Self::SCREAM_TO_CRAWL, Parameter::Rule(input.crawl))
+            .set_parameter(Self::CRAWL_TO_DYING, Parameter::Rule(input.dead))
             .evaluate_pose(animations_container, dt)
             .apply(&mut scene.graph);
     }
@@ -240,4 +329,8 @@ impl LowerBodyMachine {
             || (active_transition.is_some()
                 && root_layer.transitions().borrow(active_transition).dest() == self.walk_state)
     }
+
+    pub fn is_crawling(&self) -> bool {
+        self.machine.layers().first().unwrap().active_state() == self.crawl_state
+    }
 }
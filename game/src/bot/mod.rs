@@ -5,12 +5,17 @@ use crate::{
         lower_body::{LowerBodyMachine, LowerBodyMachineInput},
         upper_body::{UpperBodyMachine, UpperBodyMachineInput},
     },
-    character::{Character, CharacterMessage, CharacterMessageData},
+    character::{
+        Character, CharacterMessage, CharacterMessageData, FallDamageDefinition, Limb, Resistances,
+        StaminaDefinition,
+    },
     current_level_mut, current_level_ref,
     door::{door_mut, door_ref, DoorContainer},
     game_ref,
-    inventory::{Inventory, ItemEntry},
-    level::item::ItemKind,
+    inventory::Inventory,
+    loot::LOOT_TABLES,
+    ragdoll::{Ragdoll, RagdollDefinition},
+    status_effect::StatusEffectKind,
     utils::{self, is_probability_event_occurred, BodyImpactHandler},
     weapon::projectile::Damage,
 };
@@ -22,7 +27,7 @@ use fyrox::{
         futures::executor::block_on,
         math::SmoothAngle,
         pool::Handle,
-        rand::{seq::IteratorRandom, Rng},
+        rand::Rng,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
         visitor::{Visit, VisitResult, Visitor},
@@ -34,13 +39,16 @@ use fyrox::{
     rand::prelude::SliceRandom,
     scene::{
         self,
+        base::BaseBuilder,
+        collider::{ColliderBuilder, ColliderShape},
         debug::SceneDrawingContext,
         graph::{
             physics::{Intersection, RayCastOptions},
             Graph,
         },
         node::{Node, TypeUuidProvider},
-        rigidbody::RigidBody,
+        rigidbody::{RigidBody, RigidBodyBuilder},
+        transform::TransformBuilder,
         Scene,
     },
     script::{
@@ -99,6 +107,14 @@ pub enum BotHostility {
     Player = 2,
 }
 
+/// Damage taken by a single limb of a bot.
+#[derive(Debug, Visit, Default, Clone)]
+pub struct LimbState {
+    pub limb: Limb,
+    pub health: f32,
+    pub severed: bool,
+}
+
 #[derive(Debug, Visit, Default, Clone)]
 pub struct Target {
     position: Vector3<f32>,
@@ -135,6 +151,20 @@ pub struct Bot {
     threaten_timeout: f32,
     #[visit(optional)]
     animation_player: Handle<Node>,
+    #[visit(optional)]
+    #[reflect(hidden)]
+    limbs: Vec<LimbState>,
+    #[visit(optional)]
+    #[reflect(hidden)]
+    ragdoll: Ragdoll,
+    // Saved, so loading a game does not drop loot of dead bots once more.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    loot_dropped: bool,
+    // Time (in seconds) that passed since the corpse of the bot came to rest.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    corpse_time: f32,
 }
 
 impl_component_provider!(Bot, character: Character);
@@ -174,6 +204,10 @@ impl Default for Bot {
             spine: Default::default(),
             threaten_timeout: 0.0,
             animation_player: Default::default(),
+            limbs: Default::default(),
+            ragdoll: Default::default(),
+            loot_dropped: false,
+            corpse_time: 0.0,
         }
     }
 }
@@ -185,6 +219,10 @@ pub struct AttackAnimationDefinition {
     timestamp: f32,
     damage: Damage,
     speed: f32,
+    /// A limb that is used to perform the attack. The attack cannot be performed once the limb
+    /// is severed.
+    #[serde(default)]
+    pub limb: Limb,
 }
 
 #[derive(Deserialize, Debug)]
@@ -208,6 +246,22 @@ pub struct BotDefinition {
     pub idle_sounds: Vec<String>,
     pub attack_sounds: Vec<String>,
     pub hostility: BotHostility,
+    pub fall_damage: FallDamageDefinition,
+    pub stamina: StaminaDefinition,
+    pub resistances: Resistances,
+    /// Status effects that could be applied to a target of a melee attack along with
+    /// probabilities of it.
+    pub attack_effects: Vec<(StatusEffectKind, f32)>,
+    /// Amount of damage a limb can take before it is severed.
+    pub limb_health: f32,
+    /// A prefab that is attached to a physical chunk of a severed limb.
+    pub gib: Option<String>,
+    /// Movement speed of a bot that has lost a leg.
+    pub crawl_speed: f32,
+    /// Dead bots will be turned into ragdolls if set, otherwise only dying animation is played.
+    pub ragdoll: Option<RagdollDefinition>,
+    /// A name of the loot table (see `data/configs/loot.ron`) that is used to fill the inventory.
+    pub loot_table: Option<String>,
 
     // Animations.
     pub idle_animation: String,
@@ -216,6 +270,8 @@ pub struct BotDefinition {
     pub walk_animation: String,
     pub aim_animation: String,
     pub dying_animation: String,
+    pub crawl_animation: Option<String>,
+    pub crawl_animation_speed: f32,
 }
 
 #[derive(Deserialize, Default)]
@@ -235,6 +291,8 @@ lazy_static! {
 }
 
 impl Bot {
+    const CORPSE_LIFETIME: f32 = 30.0;
+
     pub fn get_definition(kind: BotKind) -> &'static BotDefinition {
         DEFINITIONS.map.get(&kind).unwrap()
     }
@@ -306,6 +364,10 @@ impl Bot {
     }
 
     pub fn can_be_removed(&self, scene: &Scene) -> bool {
+        if self.ragdoll.is_active() {
+            return self.ragdoll.is_settled();
+        }
+
         utils::fetch_animation_container_ref(&scene.graph, self.animation_player)
             .get(self.upper_body_machine.dying_animation)
             .has_ended()
@@ -329,12 +391,77 @@ impl Bot {
         self.target = Some(Target { position, handle });
     }
 
+    pub fn has_target(&self, handle: Handle<Node>) -> bool {
+        self.target.as_ref().map_or(false, |t| t.handle == handle)
+    }
+
     pub fn blow_up_head(&mut self, _graph: &mut Graph) {
         self.head_exploded = true;
 
         // TODO: Add effect.
     }
 
+    fn try_activate_ragdoll(
+        &mut self,
+        graph: &mut Graph,
+        self_handle: Handle<Node>,
+        direction: Vector3<f32>,
+    ) {
+        if let Some(ragdoll_definition) = self.definition.ragdoll.as_ref() {
+            if self.is_dead() && !self.ragdoll.is_active() {
+                self.ragdoll.activate(
+                    graph,
+                    self_handle,
+                    &self.character.hit_boxes,
+                    self.character.capsule_collider,
+                    direction.try_normalize(f32::EPSILON).unwrap_or_default()
+                        * ragdoll_definition.impulse,
+                );
+            }
+        }
+    }
+
+    pub fn is_limb_severed(&self, limb: Limb) -> bool {
+        self.limbs.iter().any(|l| l.limb == limb && l.severed)
+    }
+
+    pub fn has_severed_leg(&self) -> bool {
+        self.limbs.iter().any(|l| l.limb.is_leg() && l.severed)
+    }
+
+    fn damage_limb(
+        &mut self,
+        limb: Limb,
+        amount: f32,
+        direction: Vector3<f32>,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+    ) {
+        let limb_state = match self.limbs.iter_mut().find(|l| l.limb == limb && !l.severed) {
+            Some(limb_state) => limb_state,
+            None => return,
+        };
+
+        limb_state.health -= amount;
+
+        if limb_state.health <= 0.0 {
+            limb_state.severed = true;
+
+            if let Some(hit_box) = self.hit_boxes.iter().find(|h| h.limb == limb) {
+                let position = scene.graph[hit_box.bone].global_position();
+
+                spawn_gib(
+                    scene,
+                    resource_manager,
+                    self.definition.gib.as_deref(),
+                    position,
+                    direction.try_normalize(f32::EPSILON).unwrap_or_default() * 3.0
+                        + Vector3::new(0.0, 2.0, 0.0),
+                );
+            }
+        }
+    }
+
     pub fn on_actor_removed(&mut self, handle: Handle<Node>) {
         if let Some(target) = self.target.as_ref() {
             if target.handle == handle {
@@ -345,6 +472,7 @@ impl Bot {
 
     pub fn resolve(&mut self) {
         self.definition = Self::get_definition(self.kind);
+        self.character.resistances = &self.definition.resistances;
     }
 }
 
@@ -382,29 +510,33 @@ impl ScriptTrait for Bot {
             self.animation_player,
         ));
 
-        let possible_item = [
-            (ItemKind::Ammo, 10),
-            (ItemKind::Medkit, 1),
-            (ItemKind::Medpack, 1),
-        ];
-        let mut items =
-            if let Some((item, count)) = possible_item.iter().choose(&mut rand::thread_rng()) {
-                vec![ItemEntry {
-                    kind: *item,
-                    amount: *count,
-                }]
-            } else {
-                Default::default()
-            };
+        if let Some(loot_table) = self.definition.loot_table.as_ref() {
+            self.inventory = Inventory::from_inner(
+                LOOT_TABLES.generate(loot_table, game_ref(context.plugins).difficulty),
+            );
+        }
 
-        if self.definition.can_use_weapons {
-            items.push(ItemEntry {
-                kind: ItemKind::Ammo,
-                amount: rand::thread_rng().gen_range(32..96),
-            });
+        self.stamina = self.definition.stamina.max;
+
+        // Hit boxes of prefabs do not have limbs assigned, so take them from names of the bones.
+        for hit_box in self.character.hit_boxes.iter_mut() {
+            if hit_box.limb == Limb::None {
+                if let Some(bone) = context.scene.graph.try_get(hit_box.bone) {
+                    hit_box.limb = Limb::from_bone_name(bone.name());
+                }
+            }
         }
 
-        self.inventory = Inventory::from_inner(items);
+        if self.limbs.is_empty() {
+            self.limbs = [Limb::LeftArm, Limb::RightArm, Limb::LeftLeg, Limb::RightLeg]
+                .into_iter()
+                .map(|limb| LimbState {
+                    limb,
+                    health: self.definition.limb_health,
+                    severed: false,
+                })
+                .collect();
+        }
 
         self.agent = NavmeshAgentBuilder::new()
             .with_position(context.scene.graph[context.handle].global_position())
@@ -462,6 +594,7 @@ impl ScriptTrait for Bot {
                     hitbox,
                     critical_hit_probability: critical_shot_probability,
                     position,
+                    kind,
                 } => {
                     if let Some((character_handle, character)) =
                         dealer.as_character(&ctx.scene.graph)
@@ -489,8 +622,25 @@ impl ScriptTrait for Bot {
                                 position.direction,
                             );
                         }
+
+                        if hitbox.limb != Limb::None {
+                            self.damage_limb(
+                                hitbox.limb,
+                                amount * self.resistances.damage_factor(kind),
+                                position.map(|p| p.direction).unwrap_or_default(),
+                                ctx.scene,
+                                ctx.resource_manager,
+                            );
+                        }
                     }
 
+                    // The body flies away in the direction of the killing hit.
+                    self.try_activate_ragdoll(
+                        &mut ctx.scene.graph,
+                        ctx.handle,
+                        position.map(|p| p.direction).unwrap_or_default(),
+                    );
+
                     // Prevent spamming with grunt sounds.
                     if self.last_health - self.health > 20.0 && !self.is_dead() {
                         self.last_health = self.health;
@@ -524,6 +674,8 @@ impl ScriptTrait for Bot {
         let game = game_ref(ctx.plugins);
         let level = current_level_ref(ctx.plugins).unwrap();
 
+        let is_crawling = self.has_severed_leg();
+
         let movement_speed_factor;
         let is_attacking;
         let is_moving;
@@ -550,11 +702,16 @@ impl ScriptTrait for Bot {
                 restoration_time: self.restoration_time,
                 v_recoil: &mut self.v_recoil,
                 h_recoil: &mut self.h_recoil,
-                move_speed: self.definition.walk_speed,
+                move_speed: if is_crawling {
+                    self.definition.crawl_speed
+                } else {
+                    self.definition.walk_speed
+                },
                 threaten_timeout: &mut self.threaten_timeout,
                 sound_manager: &level.sound_manager,
                 script_message_sender: ctx.message_sender,
                 navmesh: level.navmesh,
+                limbs: &self.limbs,
 
                 // Output
                 animation_player: self.animation_player,
@@ -579,6 +736,32 @@ impl ScriptTrait for Bot {
         self.restoration_time -= ctx.dt;
         self.threaten_timeout -= ctx.dt;
 
+        if !self.is_dead() {
+            self.character.update_reload(&mut ctx.scene.graph, ctx.dt);
+            self.character.update_status_effects(ctx.dt);
+
+            // Bots "run" when they're rushing to stick to the target.
+            if is_moving && movement_speed_factor > 1.0 {
+                self.character
+                    .drain_stamina(&self.definition.stamina, ctx.dt);
+            } else {
+                self.character
+                    .regenerate_stamina(&self.definition.stamina, ctx.dt);
+            }
+
+            let has_ground_contact = self.has_ground_contact(&ctx.scene.graph);
+            if self.character.handle_landing(
+                &ctx.scene.graph,
+                has_ground_contact,
+                &self.definition.fall_damage,
+                ctx.handle,
+                ctx.message_sender,
+            ) {
+                // Hard landing stuns the bot for a while, the same way as a heavy hit does.
+                self.restoration_time = 0.8;
+            }
+        }
+
         self.check_doors(ctx.scene, &level.doors_container);
 
         self.lower_body_machine.apply(
@@ -588,6 +771,7 @@ impl ScriptTrait for Bot {
                 walk: is_moving,
                 scream: is_screaming,
                 dead: self.is_dead(),
+                crawl: is_crawling,
                 movement_speed_factor,
             },
             self.animation_player,
@@ -608,6 +792,40 @@ impl ScriptTrait for Bot {
         );
         self.impact_handler.update_and_apply(ctx.dt, ctx.scene);
 
+        // Bots could die without a hit as well, for example from bleeding.
+        self.try_activate_ragdoll(&mut ctx.scene.graph, ctx.handle, Vector3::default());
+
+        // Everything the bot had is dropped on death, no matter what killed it.
+        if self.is_dead() && !self.loot_dropped {
+            self.loot_dropped = true;
+
+            for item in self.inventory.items() {
+                ctx.message_sender.send_to_target(
+                    ctx.handle,
+                    CharacterMessage {
+                        character: ctx.handle,
+                        data: CharacterMessageData::DropItems {
+                            item: item.kind(),
+                            count: item.amount(),
+                        },
+                    },
+                );
+            }
+        }
+        if let Some(ragdoll_definition) = self.definition.ragdoll.as_ref() {
+            self.ragdoll
+                .update(&mut ctx.scene.graph, ragdoll_definition.blend_time, ctx.dt);
+        }
+
+        // Corpses are removed some time after they came to rest, so they won't pile up.
+        if self.is_dead() && self.can_be_removed(ctx.scene) {
+            self.corpse_time += ctx.dt;
+            if self.corpse_time >= Self::CORPSE_LIFETIME {
+                ctx.scene.graph.remove_node(ctx.handle);
+                return;
+            }
+        }
+
         self.v_recoil.update(ctx.dt);
         self.h_recoil.update(ctx.dt);
 
@@ -630,6 +848,15 @@ impl ScriptTrait for Bot {
                     .set_scale(Vector3::new(0.0, 0.0, 0.0));
             }
         }
+
+        // Animations override transforms of bones, so severed limbs must be hidden every frame.
+        for hit_box in self.hit_boxes.iter() {
+            if self.is_limb_severed(hit_box.limb) {
+                ctx.scene.graph[hit_box.bone]
+                    .local_transform_mut()
+                    .set_scale(Vector3::new(0.0, 0.0, 0.0));
+            }
+        }
     }
 
     fn id(&self) -> Uuid {
@@ -637,6 +864,46 @@ impl ScriptTrait for Bot {
     }
 }
 
+/// Spawns a physical chunk of a severed limb and throws it with the given velocity.
+fn spawn_gib(
+    scene: &mut Scene,
+    resource_manager: &ResourceManager,
+    prefab: Option<&str>,
+    position: Vector3<f32>,
+    velocity: Vector3<f32>,
+) -> Handle<Node> {
+    let mut children = vec![ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::capsule_y(0.15, 0.06))
+        .build(&mut scene.graph)];
+
+    if let Some(prefab) = prefab {
+        if let Ok(model) = block_on(resource_manager.request_model(prefab)) {
+            children.push(model.instantiate(scene));
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_lifetime(20.0)
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&children),
+    )
+    .with_mass(2.0)
+    .with_lin_vel(velocity)
+    .with_ang_vel(Vector3::new(
+        rng.gen_range(-5.0..5.0),
+        rng.gen_range(-5.0..5.0),
+        rng.gen_range(-5.0..5.0),
+    ))
+    .build(&mut scene.graph)
+}
+
 pub fn try_get_bot_mut(handle: Handle<Node>, graph: &mut Graph) -> Option<&mut Bot> {
     graph
         .try_get_mut(handle)
@@ -1,10 +1,18 @@
 use crate::weapon::{WeaponMessage, WeaponMessageData};
 use crate::{
     block_on,
+    container::Container,
+    gui::journal::Journal,
     inventory::Inventory,
+    item_effect::ItemEffectRegistry,
     level::item::{item_mut, ItemKind},
+    player::equipment::{Equipment, EquipmentDefinition},
     sound::{SoundKind, SoundManager},
-    weapon::{definition::WeaponKind, weapon_mut, weapon_ref},
+    status_effect::{StatusEffectKind, StatusEffects},
+    weapon::{
+        definition::WeaponKind, projectile::DamageType, try_weapon_mut, try_weapon_ref, weapon_mut,
+        weapon_ref,
+    },
     Item, Weapon,
 };
 use fyrox::{
@@ -20,10 +28,14 @@ use fyrox::{
         collider::Collider,
         graph::{map::NodeHandleMap, physics::RayCastOptions, Graph},
         node::Node,
+        rigidbody::RigidBody,
         Scene,
     },
     script::ScriptMessageSender,
+    utils::log::Log,
 };
+use serde::Deserialize;
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 #[derive(Copy, Clone)]
 pub struct DamageDealer {
@@ -68,6 +80,8 @@ pub enum CharacterMessageData {
         hitbox: Option<HitBox>,
         /// Numeric value of damage.
         amount: f32,
+        /// Type of damage, it is used to apply resistances of the target.
+        kind: DamageType,
         /// Only takes effect iff damage was applied to a head hit box!
         critical_hit_probability: f32,
         position: Option<DamagePosition>,
@@ -79,6 +93,19 @@ pub enum CharacterMessageData {
         item: ItemKind,
         count: u32,
     },
+    /// Moves items from a container to the inventory of the character.
+    TakeFromContainer {
+        container: Handle<Node>,
+        item: ItemKind,
+        count: u32,
+    },
+    /// Moves items from the inventory of the character to a container.
+    PutToContainer {
+        container: Handle<Node>,
+        item: ItemKind,
+        count: u32,
+    },
+    ApplyStatusEffect(StatusEffectKind),
 }
 
 pub struct CharacterMessage {
@@ -86,6 +113,88 @@ pub struct CharacterMessage {
     pub data: CharacterMessageData,
 }
 
+/// Defines how much damage a character takes when it lands after a fall. All speeds are vertical
+/// speeds at the moment of touchdown, given in m/s.
+#[derive(Deserialize, Debug, Clone)]
+pub struct FallDamageDefinition {
+    /// Maximum speed at which landing is still harmless.
+    pub safe_speed: f32,
+    /// Amount of damage for each m/s above the safe speed.
+    pub damage_per_speed: f32,
+    /// Minimum speed at which landing is hard enough to stun the character.
+    pub stun_speed: f32,
+}
+
+/// Defines how fast a character spends and restores its stamina. Rates are given per second.
+#[derive(Deserialize, Debug, Clone)]
+pub struct StaminaDefinition {
+    pub max: f32,
+    pub run_drain_rate: f32,
+    pub jump_cost: f32,
+    pub melee_cost: f32,
+    pub regeneration_rate: f32,
+    /// An exhausted character is not able to run until its stamina restores to this value.
+    pub recovery_threshold: f32,
+}
+
+/// Defines how much armor a character could have and which part of incoming damage the armor
+/// absorbs before health.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ArmorDefinition {
+    pub max: f32,
+    /// Part of incoming damage (from 0 to 1) that is taken by the armor.
+    pub absorption: f32,
+}
+
+impl ArmorDefinition {
+    pub const NONE: Self = Self {
+        max: 0.0,
+        absorption: 0.0,
+    };
+}
+
+/// Defines which part of damage of each type a character absorbs. Negative values make a character
+/// vulnerable to the damage type. Missing values are treated as zero.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Resistances {
+    pub ballistic: f32,
+    pub energy: f32,
+    pub explosive: f32,
+    pub fire: f32,
+    pub melee: f32,
+    pub crush: f32,
+}
+
+impl Resistances {
+    pub const NONE: Self = Self {
+        ballistic: 0.0,
+        energy: 0.0,
+        explosive: 0.0,
+        fire: 0.0,
+        melee: 0.0,
+        crush: 0.0,
+    };
+
+    pub fn damage_factor(&self, kind: DamageType) -> f32 {
+        let resistance = match kind {
+            DamageType::Ballistic => self.ballistic,
+            DamageType::Energy => self.energy,
+            DamageType::Explosive => self.explosive,
+            DamageType::Fire => self.fire,
+            DamageType::Melee => self.melee,
+            DamageType::Crush => self.crush,
+        };
+        (1.0 - resistance).max(0.0)
+    }
+}
+
+impl FallDamageDefinition {
+    pub fn damage(&self, speed: f32) -> f32 {
+        (speed - self.safe_speed).max(0.0) * self.damage_per_speed
+    }
+}
+
 #[derive(Visit, Reflect, Debug, Clone)]
 pub struct Character {
     pub capsule_collider: Handle<Node>,
@@ -98,6 +207,29 @@ pub struct Character {
     #[visit(optional)]
     pub hit_boxes: Vec<HitBox>,
     pub inventory: Inventory,
+    #[visit(optional)]
+    pub stamina: f32,
+    #[visit(optional)]
+    #[reflect(hidden)]
+    exhausted: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    fall_speed: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub resistances: &'static Resistances,
+    #[visit(optional)]
+    #[reflect(hidden)]
+    pub status_effects: StatusEffects,
+    #[visit(optional)]
+    pub armor: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub armor_definition: &'static ArmorDefinition,
+    // Multiplier of the volume of footsteps.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub footstep_gain: f32,
 }
 
 impl Default for Character {
@@ -112,6 +244,14 @@ impl Default for Character {
             weapon_pivot: Handle::NONE,
             hit_boxes: Default::default(),
             inventory: Default::default(),
+            stamina: 100.0,
+            exhausted: false,
+            fall_speed: 0.0,
+            resistances: &Resistances::NONE,
+            status_effects: Default::default(),
+            armor: 0.0,
+            armor_definition: &ArmorDefinition::NONE,
+            footstep_gain: 1.0,
         }
     }
 }
@@ -138,6 +278,91 @@ impl Character {
         false
     }
 
+    /// Tracks vertical speed of the character while it is in the air and applies fall damage on
+    /// touchdown. Returns `true` if the landing was hard enough to stun the character.
+    pub fn handle_landing(
+        &mut self,
+        graph: &Graph,
+        has_ground_contact: bool,
+        fall_damage: &FallDamageDefinition,
+        self_handle: Handle<Node>,
+        script_message_sender: &ScriptMessageSender,
+    ) -> bool {
+        if !has_ground_contact {
+            if let Some(body) = graph.try_get_of_type::<RigidBody>(self.body) {
+                // The solver zeroes the velocity at the frame the contact appears, so remember the
+                // highest speed reached during the fall instead.
+                self.fall_speed = self.fall_speed.max(-body.lin_vel().y);
+            }
+            return false;
+        }
+
+        let speed = std::mem::take(&mut self.fall_speed);
+
+        let amount = fall_damage.damage(speed);
+        if amount > 0.0 {
+            script_message_sender.send_to_target(
+                self_handle,
+                CharacterMessage {
+                    character: self_handle,
+                    data: CharacterMessageData::Damage {
+                        dealer: DamageDealer {
+                            entity: Default::default(),
+                        },
+                        hitbox: None,
+                        amount,
+                        kind: DamageType::Crush,
+                        critical_hit_probability: 0.0,
+                        position: None,
+                    },
+                },
+            );
+        }
+
+        speed >= fall_damage.stun_speed
+    }
+
+    /// Applies damage of lingering status effects and removes expired ones.
+    pub fn update_status_effects(&mut self, dt: f32) {
+        let damage = self.status_effects.update(self.resistances, dt);
+        if damage > 0.0 {
+            self.damage(damage);
+        }
+    }
+
+    /// Spends given amount of stamina at once. Returns `false` if there is not enough stamina, in
+    /// this case nothing is spent.
+    pub fn try_spend_stamina(&mut self, amount: f32) -> bool {
+        if self.stamina >= amount {
+            self.stamina -= amount;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn drain_stamina(&mut self, definition: &StaminaDefinition, dt: f32) {
+        self.stamina = (self.stamina - definition.run_drain_rate * dt).max(0.0);
+        if self.stamina <= 0.0 {
+            self.exhausted = true;
+        }
+    }
+
+    pub fn restore_stamina(&mut self, amount: f32, definition: &StaminaDefinition) {
+        self.stamina = (self.stamina + amount.abs()).min(definition.max);
+    }
+
+    pub fn regenerate_stamina(&mut self, definition: &StaminaDefinition, dt: f32) {
+        self.stamina = (self.stamina + definition.regeneration_rate * dt).min(definition.max);
+        if self.stamina >= definition.recovery_threshold {
+            self.exhausted = false;
+        }
+    }
+
+    pub fn can_run(&self) -> bool {
+        !self.exhausted
+    }
+
     pub fn get_health(&self) -> f32 {
         self.health
     }
@@ -156,6 +381,20 @@ impl Character {
         self.health -= amount.abs();
     }
 
+    /// Lets the armor take its part of the damage. Returns the rest of the damage that should be
+    /// applied to health.
+    pub fn absorb_damage(&mut self, amount: f32) -> f32 {
+        let absorbed = (amount * self.armor_definition.absorption)
+            .min(self.armor)
+            .max(0.0);
+        self.armor -= absorbed;
+        amount - absorbed
+    }
+
+    pub fn repair_armor(&mut self, amount: f32) {
+        self.armor = (self.armor + amount.abs()).min(self.armor_definition.max);
+    }
+
     pub fn heal(&mut self, amount: f32) {
         self.health += amount.abs();
 
@@ -178,7 +417,7 @@ impl Character {
 
     pub fn add_weapon(&mut self, weapon: Handle<Node>, graph: &mut Graph) {
         for &other_weapon in self.weapons.iter() {
-            graph[other_weapon].set_enabled(false);
+            set_weapon_enabled(other_weapon, false, graph);
         }
 
         self.current_weapon = self.weapons.len() as u32;
@@ -187,20 +426,18 @@ impl Character {
         self.set_current_weapon_enabled(true, graph);
     }
 
-    pub fn use_item(&mut self, kind: ItemKind) {
-        match kind {
-            ItemKind::Medkit => self.heal(40.0),
-            ItemKind::Medpack => self.heal(20.0),
-            // Non-consumable items.
-            ItemKind::Ak47
-            | ItemKind::PlasmaGun
-            | ItemKind::M4
-            | ItemKind::Glock
-            | ItemKind::Ammo
-            | ItemKind::RailGun
-            | ItemKind::Grenade
-            | ItemKind::MasterKey => (),
-        }
+    /// Applies effects of the item (see `ItemEffectRegistry`) and cures status effects that could
+    /// be cured by the item.
+    pub fn use_item(
+        &mut self,
+        kind: ItemKind,
+        stamina: &StaminaDefinition,
+        journal: Option<&mut Journal>,
+        equipment: Option<(&mut Equipment, &EquipmentDefinition)>,
+    ) {
+        self.status_effects.cure_with(kind);
+
+        ItemEffectRegistry::apply(kind, self, stamina, journal, equipment);
     }
 
     pub fn on_weapon_message(&mut self, weapon_message: &WeaponMessage, graph: &mut Graph) {
@@ -229,33 +466,70 @@ impl Character {
         sound_manager: &SoundManager,
     ) {
         match message_data {
-            CharacterMessageData::Damage { amount, .. } => {
-                self.damage(*amount);
+            CharacterMessageData::Damage { amount, kind, .. } => {
+                let amount = self.absorb_damage(*amount * self.resistances.damage_factor(*kind));
+                self.damage(amount);
+
+                if !self.is_dead() {
+                    self.status_effects.apply_caused_by(*kind);
+                }
+            }
+            &CharacterMessageData::ApplyStatusEffect(kind) => {
+                if !self.is_dead() {
+                    self.status_effects.apply(kind);
+                }
             }
             CharacterMessageData::SelectWeapon(kind) => self.select_weapon(*kind, &mut scene.graph),
             CharacterMessageData::AddWeapon(kind) => {
-                let weapon = block_on(
-                    resource_manager.request_model(Weapon::definition(*kind).model.clone()),
-                )
-                .unwrap()
-                .instantiate(scene);
+                let path = &Weapon::definition(*kind).model;
+                let weapon = match block_on(resource_manager.request_model(path)) {
+                    Ok(model) => model.instantiate(scene),
+                    Err(_) => {
+                        // Broken weapons.ron must not crash the game, the weapon is just skipped.
+                        Log::err(format!(
+                            "[Character]: Unable to load model {} of weapon {:?}!",
+                            path, kind
+                        ));
+                        return;
+                    }
+                };
 
                 // Root node must have Weapon script.
                 assert!(scene.graph[weapon].has_script::<Weapon>());
 
-                weapon_mut(weapon, &mut scene.graph).set_owner(self_handle);
+                let weapon_script = weapon_mut(weapon, &mut scene.graph);
+                weapon_script.set_owner(self_handle);
+                // New weapon comes loaded with whatever ammo the character has.
+                weapon_script.load_from(&mut self.inventory);
 
                 self.add_weapon(weapon, &mut scene.graph);
                 scene.graph.link_nodes(weapon, self.weapon_pivot());
-                self.inventory_mut().add_item(kind.associated_item(), 1);
+                Log::verify(self.inventory_mut().add_item(kind.associated_item(), 1));
             }
             &CharacterMessageData::PickupItem(item_handle) => {
                 let position = scene.graph[item_handle].global_position();
                 let item = item_mut(item_handle, &mut scene.graph);
-
                 let kind = item.get_kind();
+                let stack_size = item.stack_size.max(1);
+
+                // Take as many items of the stack as the inventory could fit, the rest stays in
+                // the world.
+                let taken = self.take_items(
+                    kind,
+                    stack_size,
+                    self_handle,
+                    &scene.graph,
+                    script_message_sender,
+                );
+                if taken == 0 {
+                    return;
+                }
 
-                scene.graph.remove_node(item_handle);
+                if taken == stack_size {
+                    scene.graph.remove_node(item_handle);
+                } else {
+                    item_mut(item_handle, &mut scene.graph).stack_size = stack_size - taken;
+                }
 
                 sound_manager.play_sound(
                     &mut scene.graph,
@@ -265,70 +539,167 @@ impl Character {
                     3.0,
                     2.0,
                 );
-
-                match kind {
-                    ItemKind::Medkit => self.inventory.add_item(ItemKind::Medkit, 1),
-                    ItemKind::Medpack => self.inventory.add_item(ItemKind::Medpack, 1),
-                    ItemKind::Ak47
-                    | ItemKind::PlasmaGun
-                    | ItemKind::M4
-                    | ItemKind::Glock
-                    | ItemKind::RailGun => {
-                        let weapon_kind = kind.associated_weapon().unwrap();
-
-                        let mut found = false;
-                        for weapon_handle in self.weapons.iter() {
-                            let weapon = weapon_ref(*weapon_handle, &scene.graph);
-                            if weapon.kind() == weapon_kind {
-                                found = true;
-                                break;
-                            }
-                        }
-                        if found {
-                            self.inventory.add_item(ItemKind::Ammo, 24);
-                        } else {
-                            // Finally if actor does not have such weapon, give new one to him.
-                            script_message_sender.send_to_target(
-                                self_handle,
-                                CharacterMessage {
-                                    character: self_handle,
-                                    data: CharacterMessageData::AddWeapon(weapon_kind),
-                                },
-                            );
-                        }
-                    }
-                    ItemKind::Ammo => {
-                        self.inventory.add_item(ItemKind::Ammo, 24);
-                    }
-                    ItemKind::Grenade => {
-                        self.inventory.add_item(ItemKind::Grenade, 1);
-                    }
-                    ItemKind::MasterKey => {
-                        self.inventory.add_item(ItemKind::MasterKey, 1);
-                    }
-                }
             }
             &CharacterMessageData::DropItems { item, count } => {
                 let drop_position = self.position(&scene.graph) + Vector3::new(0.0, 0.5, 0.0);
-                let weapons = self.weapons().to_vec();
 
                 if self.inventory.try_extract_exact_items(item, count) == count {
-                    // Make sure to remove weapons associated with items.
-                    if let Some(weapon_kind) = item.associated_weapon() {
-                        for weapon in weapons {
-                            if weapon_ref(weapon, &scene.graph).kind() == weapon_kind {
-                                scene.graph.remove_node(weapon);
-                            }
-                        }
-                    }
+                    self.remove_associated_weapons(item, scene, resource_manager);
+
+                    Item::add_to_scene(
+                        scene,
+                        resource_manager.clone(),
+                        item,
+                        count,
+                        drop_position,
+                        true,
+                    );
+                }
+            }
+            &CharacterMessageData::TakeFromContainer {
+                container,
+                item,
+                count,
+            } => {
+                let available = match scene
+                    .graph
+                    .try_get(container)
+                    .and_then(|c| c.try_get_script::<Container>())
+                {
+                    Some(container) => container.inventory().item_count(item).min(count),
+                    None => return,
+                };
+
+                let taken = self.take_items(
+                    item,
+                    available,
+                    self_handle,
+                    &scene.graph,
+                    script_message_sender,
+                );
 
-                    Item::add_to_scene(scene, resource_manager.clone(), item, drop_position, true);
+                if let Some(container) = scene
+                    .graph
+                    .try_get_mut(container)
+                    .and_then(|c| c.try_get_script_mut::<Container>())
+                {
+                    container
+                        .inventory_mut()
+                        .try_extract_exact_items(item, taken);
+                }
+            }
+            &CharacterMessageData::PutToContainer {
+                container,
+                item,
+                count,
+            } => {
+                let container = match scene
+                    .graph
+                    .try_get_mut(container)
+                    .and_then(|c| c.try_get_script_mut::<Container>())
+                {
+                    Some(container) => container,
+                    None => return,
+                };
+
+                let count = count
+                    .min(self.inventory.item_count(item))
+                    .min(container.inventory().max_addable(item));
+
+                if count > 0 && self.inventory.try_extract_exact_items(item, count) == count {
+                    Log::verify(container.inventory_mut().add_item(item, count));
+
+                    self.remove_associated_weapons(item, scene, resource_manager);
                 }
             }
             _ => (),
         }
     }
 
+    /// Adds at most `available` items of the given kind (lying in the world or stored in a
+    /// container) to the inventory and returns how many of them were taken. New weapons are
+    /// created with `AddWeapon` message.
+    fn take_items(
+        &mut self,
+        kind: ItemKind,
+        available: u32,
+        self_handle: Handle<Node>,
+        graph: &Graph,
+        script_message_sender: &ScriptMessageSender,
+    ) -> u32 {
+        let (added_kind, amount_per_item) = self.pickup_items(kind, graph);
+        let is_new_weapon = kind.associated_weapon().is_some() && added_kind == kind;
+        let mut taken = available.min(self.inventory.max_addable(added_kind) / amount_per_item);
+        if is_new_weapon {
+            // A character could not have two weapons of the same kind.
+            taken = taken.min(1);
+        }
+
+        if taken > 0 {
+            if is_new_weapon {
+                // Finally if actor does not have such weapon, give new one to him.
+                script_message_sender.send_to_target(
+                    self_handle,
+                    CharacterMessage {
+                        character: self_handle,
+                        data: CharacterMessageData::AddWeapon(kind.associated_weapon().unwrap()),
+                    },
+                );
+            } else {
+                Log::verify(self.inventory.add_item(added_kind, taken * amount_per_item));
+            }
+        }
+
+        taken
+    }
+
+    /// Removes weapons associated with the given item, ammo left in their magazines goes back to
+    /// the inventory. Ammo that does not fit in the inventory is dropped next to the character.
+    fn remove_associated_weapons(
+        &mut self,
+        item: ItemKind,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+    ) {
+        if let Some(weapon_kind) = item.associated_weapon() {
+            for weapon in self.weapons().to_vec() {
+                if weapon_ref(weapon, &scene.graph).kind() == weapon_kind {
+                    let ammo = weapon_mut(weapon, &mut scene.graph).unload();
+                    if ammo > 0 && self.inventory.add_item(ItemKind::Ammo, ammo).is_err() {
+                        Item::add_to_scene(
+                            scene,
+                            resource_manager.clone(),
+                            ItemKind::Ammo,
+                            ammo,
+                            self.position(&scene.graph) + Vector3::new(0.0, 0.5, 0.0),
+                            true,
+                        );
+                    }
+                    scene.graph.remove_node(weapon);
+                }
+            }
+        }
+    }
+
+    /// Returns kind and amount of inventory items that a single world item of the given kind
+    /// gives. Weapons that the character already has are turned into a magazine of ammo.
+    pub fn pickup_items(&self, kind: ItemKind, graph: &Graph) -> (ItemKind, u32) {
+        match kind.associated_weapon() {
+            Some(weapon_kind)
+                if self
+                    .weapons
+                    .iter()
+                    .any(|&w| weapon_ref(w, graph).kind() == weapon_kind) =>
+            {
+                (
+                    ItemKind::Ammo,
+                    Weapon::definition(weapon_kind).magazine_capacity,
+                )
+            }
+            _ => (kind, 1),
+        }
+    }
+
     pub fn select_weapon(&mut self, weapon: WeaponKind, graph: &mut Graph) {
         if let Some(index) = self
             .weapons
@@ -336,7 +707,7 @@ impl Character {
             .position(|&w| weapon_ref(w, graph).kind() == weapon)
         {
             for &other_weapon in self.weapons.iter() {
-                graph[other_weapon].set_enabled(false);
+                set_weapon_enabled(other_weapon, false, graph);
             }
 
             self.current_weapon = index as u32;
@@ -354,10 +725,34 @@ impl Character {
 
     fn set_current_weapon_enabled(&self, state: bool, graph: &mut Graph) {
         if let Some(current_weapon) = self.weapons.get(self.current_weapon as usize) {
-            graph[*current_weapon].set_enabled(state);
+            set_weapon_enabled(*current_weapon, state, graph);
+        }
+    }
+
+    /// Starts reloading of current weapon. Returns `false` if the weapon cannot be reloaded - its
+    /// magazine is full or there's no ammo in the inventory.
+    pub fn try_begin_reload(&mut self, graph: &mut Graph) -> bool {
+        let inventory = &self.inventory;
+        try_weapon_mut(self.current_weapon(), graph)
+            .map_or(false, |weapon| weapon.begin_reload(inventory))
+    }
+
+    pub fn update_reload(&mut self, graph: &mut Graph, dt: f32) {
+        if let Some(weapon) = try_weapon_mut(self.current_weapon(), graph) {
+            weapon.update_reload(&mut self.inventory, dt);
         }
     }
 
+    pub fn finish_reload(&mut self, graph: &mut Graph) {
+        if let Some(weapon) = try_weapon_mut(self.current_weapon(), graph) {
+            weapon.finish_reload(&mut self.inventory);
+        }
+    }
+
+    pub fn is_reloading(&self, graph: &Graph) -> bool {
+        try_weapon_ref(self.current_weapon(), graph).map_or(false, |w| w.is_reloading())
+    }
+
     pub fn next_weapon(&mut self, graph: &mut Graph) {
         if !self.weapons.is_empty() && (self.current_weapon as usize) < self.weapons.len() - 1 {
             self.set_current_weapon_enabled(false, graph);
@@ -437,7 +832,7 @@ impl Character {
                 intersection.feature,
                 intersection.position.coords,
                 SoundKind::FootStep,
-                0.2,
+                0.2 * self.footstep_gain,
                 1.0,
                 0.3,
             );
@@ -445,6 +840,57 @@ impl Character {
     }
 }
 
+/// A part of a body that could be severed.
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Deserialize,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum Limb {
+    None,
+    LeftArm,
+    RightArm,
+    LeftLeg,
+    RightLeg,
+}
+
+impl Default for Limb {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl Limb {
+    pub fn is_leg(self) -> bool {
+        matches!(self, Limb::LeftLeg | Limb::RightLeg)
+    }
+
+    /// Finds a limb by name of a bone of a Mixamo skeleton ("mixamorig:LeftForeArm" and so on).
+    pub fn from_bone_name(name: &str) -> Self {
+        let name = name.rsplit(':').next().unwrap_or(name);
+        let is_arm = ["Arm", "Hand"].iter().any(|part| name.contains(part));
+        let is_leg = ["Leg", "Foot", "Toe"]
+            .iter()
+            .any(|part| name.contains(part));
+        match (name.starts_with("Left"), name.starts_with("Right")) {
+            (true, _) if is_arm => Limb::LeftArm,
+            (true, _) if is_leg => Limb::LeftLeg,
+            (_, true) if is_arm => Limb::RightArm,
+            (_, true) if is_leg => Limb::RightLeg,
+            _ => Limb::None,
+        }
+    }
+}
+
 #[derive(Default, Clone, Copy, PartialEq, Debug, Visit, Reflect)]
 pub struct HitBox {
     pub bone: Handle<Node>,
@@ -452,6 +898,8 @@ pub struct HitBox {
     pub damage_factor: f32,
     pub movement_speed_factor: f32,
     pub is_head: bool,
+    #[visit(optional)]
+    pub limb: Limb,
 }
 
 impl HitBox {
@@ -481,3 +929,14 @@ pub fn try_get_character_mut(handle: Handle<Node>, graph: &mut Graph) -> Option<
 pub fn character_mut(handle: Handle<Node>, graph: &mut Graph) -> &mut Character {
     try_get_character_mut(handle, graph).unwrap()
 }
+
+fn set_weapon_enabled(weapon: Handle<Node>, state: bool, graph: &mut Graph) {
+    graph[weapon].set_enabled(state);
+
+    if !state {
+        // Hidden weapon cannot be reloaded.
+        if let Some(weapon) = try_weapon_mut(weapon, graph) {
+            weapon.cancel_reload();
+        }
+    }
+}
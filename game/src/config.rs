@@ -1,15 +1,27 @@
-use crate::control_scheme::ControlScheme;
+use crate::{control_scheme::ControlScheme, sound::SoundCategory};
 use fyrox::engine::GraphicsContext;
 use fyrox::{plugin::PluginContext, renderer::QualitySettings};
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 
+fn default_bus_volume() -> f32 {
+    1.0
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct SoundConfig {
     pub master_volume: f32,
     pub music_volume: f32,
     pub use_hrtf: bool,
+    #[serde(default = "default_bus_volume")]
+    pub sfx_volume: f32,
+    #[serde(default = "default_bus_volume")]
+    pub voice_volume: f32,
+    #[serde(default = "default_bus_volume")]
+    pub ui_volume: f32,
+    #[serde(default = "default_bus_volume")]
+    pub ambience_volume: f32,
 }
 
 impl Default for SoundConfig {
@@ -18,8 +30,48 @@ impl Default for SoundConfig {
             master_volume: 1.0,
             music_volume: 0.5,
             use_hrtf: true,
+            sfx_volume: 1.0,
+            voice_volume: 1.0,
+            ui_volume: 1.0,
+            ambience_volume: 1.0,
+        }
+    }
+}
+
+impl SoundConfig {
+    /// Returns volume of the audio bus of the given category.
+    pub fn volume(&self, category: SoundCategory) -> f32 {
+        match category {
+            SoundCategory::Sfx => self.sfx_volume,
+            SoundCategory::Voice => self.voice_volume,
+            SoundCategory::Ui => self.ui_volume,
+            SoundCategory::Ambience => self.ambience_volume,
+            SoundCategory::Music => self.music_volume,
         }
     }
+
+    pub fn set_volume(&mut self, category: SoundCategory, volume: f32) {
+        match category {
+            SoundCategory::Sfx => self.sfx_volume = volume,
+            SoundCategory::Voice => self.voice_volume = volume,
+            SoundCategory::Ui => self.ui_volume = volume,
+            SoundCategory::Ambience => self.ambience_volume = volume,
+            SoundCategory::Music => self.music_volume = volume,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::Normal
+    }
 }
 
 #[derive(Deserialize, Serialize, Default, Clone)]
@@ -28,6 +80,8 @@ pub struct Config {
     pub controls: ControlScheme,
     pub sound: SoundConfig,
     pub show_debug_info: bool,
+    #[serde(default)]
+    pub difficulty: Difficulty,
 }
 
 #[derive(Debug)]
@@ -62,6 +116,7 @@ impl Config {
         control_scheme: ControlScheme,
         sound_config: SoundConfig,
         show_debug_info: bool,
+        difficulty: Difficulty,
     ) -> Result<(), ConfigError> {
         if let GraphicsContext::Initialized(ref graphics_context) = context.graphics_context {
             let config = Self {
@@ -69,6 +124,7 @@ impl Config {
                 controls: control_scheme,
                 sound: sound_config,
                 show_debug_info,
+                difficulty,
             };
             let file = File::create(Self::PATH)?;
             ron::ser::to_writer_pretty(file, &config, PrettyConfig::default())?;
@@ -0,0 +1,95 @@
+//! Containers (lockers, crates, corpses) hold items that are generated from a loot table when the
+//! level starts. The player could open a container with `action` to transfer items between the
+//! container and the inventory.
+
+use crate::{current_level_mut, game_ref, inventory::Inventory, loot::LOOT_TABLES};
+use fyrox::{
+    core::{
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    impl_component_provider,
+    scene::node::TypeUuidProvider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct Container {
+    #[reflect(
+        description = "A name of the loot table (see data/configs/loot.ron) that is used to fill the container."
+    )]
+    loot_table: String,
+
+    #[reflect(description = "Locked containers could be opened only with a key.")]
+    #[visit(optional)]
+    locked: bool,
+
+    #[reflect(hidden)]
+    #[visit(optional)]
+    inventory: Inventory,
+
+    // Loot is generated only once, so saved games keep whatever is left in the container.
+    #[reflect(hidden)]
+    #[visit(optional)]
+    generated: bool,
+}
+
+impl_component_provider!(Container);
+
+impl TypeUuidProvider for Container {
+    fn type_uuid() -> Uuid {
+        uuid!("70f16132-fb1c-4b8f-af98-bcc9a6cebc69")
+    }
+}
+
+impl Container {
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    pub fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+}
+
+impl ScriptTrait for Container {
+    fn on_init(&mut self, ctx: &mut ScriptContext) {
+        current_level_mut(ctx.plugins)
+            .expect("Level must exist!")
+            .containers
+            .push(ctx.handle);
+    }
+
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        if !self.generated {
+            self.generated = true;
+
+            if !self.loot_table.is_empty() {
+                self.inventory = Inventory::from_inner(
+                    LOOT_TABLES.generate(&self.loot_table, game_ref(ctx.plugins).difficulty),
+                );
+            }
+        }
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        if let Some(level) = current_level_mut(ctx.plugins) {
+            if let Some(position) = level.containers.iter().position(|c| *c == ctx.node_handle) {
+                level.containers.remove(position);
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
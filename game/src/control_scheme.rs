@@ -33,6 +33,42 @@ pub struct ControlButtonDefinition {
     pub button: ControlButton,
 }
 
+// Buttons that were added after the first release have defaults, so older settings still load.
+fn default_melee() -> ControlButtonDefinition {
+    ControlButtonDefinition {
+        description: "Melee Attack".to_string(),
+        button: ControlButton::Key(VirtualKeyCode::V),
+    }
+}
+
+fn default_reload() -> ControlButtonDefinition {
+    ControlButtonDefinition {
+        description: "Reload".to_string(),
+        button: ControlButton::Key(VirtualKeyCode::T),
+    }
+}
+
+fn default_cycle_fire_mode() -> ControlButtonDefinition {
+    ControlButtonDefinition {
+        description: "Fire Mode".to_string(),
+        button: ControlButton::Key(VirtualKeyCode::B),
+    }
+}
+
+fn default_cycle_grenade() -> ControlButtonDefinition {
+    ControlButtonDefinition {
+        description: "Cycle Grenade".to_string(),
+        button: ControlButton::Key(VirtualKeyCode::H),
+    }
+}
+
+fn default_combine() -> ControlButtonDefinition {
+    ControlButtonDefinition {
+        description: "Combine Items".to_string(),
+        button: ControlButton::Key(VirtualKeyCode::C),
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct ControlScheme {
     pub move_forward: ControlButtonDefinition,
@@ -41,11 +77,19 @@ pub struct ControlScheme {
     pub move_right: ControlButtonDefinition,
     pub jump: ControlButtonDefinition,
     pub shoot: ControlButtonDefinition,
+    #[serde(default = "default_melee")]
+    pub melee: ControlButtonDefinition,
+    #[serde(default = "default_reload")]
+    pub reload: ControlButtonDefinition,
+    #[serde(default = "default_cycle_fire_mode")]
+    pub cycle_fire_mode: ControlButtonDefinition,
     pub next_weapon: ControlButtonDefinition,
     pub prev_weapon: ControlButtonDefinition,
     pub run: ControlButtonDefinition,
     pub aim: ControlButtonDefinition,
     pub toss_grenade: ControlButtonDefinition,
+    #[serde(default = "default_cycle_grenade")]
+    pub cycle_grenade: ControlButtonDefinition,
     pub journal: ControlButtonDefinition,
     pub flash_light: ControlButtonDefinition,
     pub grab_ak47: ControlButtonDefinition,
@@ -55,6 +99,8 @@ pub struct ControlScheme {
     pub inventory: ControlButtonDefinition,
     pub action: ControlButtonDefinition,
     pub drop_item: ControlButtonDefinition,
+    #[serde(default = "default_combine")]
+    pub combine: ControlButtonDefinition,
     pub cursor_up: ControlButtonDefinition,
     pub cursor_down: ControlButtonDefinition,
     pub cursor_left: ControlButtonDefinition,
@@ -90,6 +136,9 @@ impl Default for ControlScheme {
                 description: "Shoot".to_string(),
                 button: ControlButton::Mouse(1),
             },
+            melee: default_melee(),
+            reload: default_reload(),
+            cycle_fire_mode: default_cycle_fire_mode(),
             next_weapon: ControlButtonDefinition {
                 description: "Next Weapon".to_string(),
                 button: ControlButton::WheelUp,
@@ -110,6 +159,7 @@ impl Default for ControlScheme {
                 description: "Toss Grenade".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::G),
             },
+            cycle_grenade: default_cycle_grenade(),
             journal: ControlButtonDefinition {
                 description: "Journal".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::J),
@@ -146,6 +196,7 @@ impl Default for ControlScheme {
                 description: "Drop Item".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::R),
             },
+            combine: default_combine(),
             cursor_up: ControlButtonDefinition {
                 description: "Cursor Up".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::Up),
@@ -169,7 +220,7 @@ impl Default for ControlScheme {
 }
 
 impl ControlScheme {
-    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 24] {
+    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 29] {
         [
             &mut self.move_forward,
             &mut self.move_backward,
@@ -177,14 +228,19 @@ impl ControlScheme {
             &mut self.move_right,
             &mut self.action,
             &mut self.drop_item,
+            &mut self.combine,
             &mut self.jump,
             &mut self.shoot,
+            &mut self.melee,
+            &mut self.reload,
+            &mut self.cycle_fire_mode,
             &mut self.next_weapon,
             &mut self.prev_weapon,
             &mut self.run,
             &mut self.aim,
             &mut self.inventory,
             &mut self.toss_grenade,
+            &mut self.cycle_grenade,
             &mut self.journal,
             &mut self.flash_light,
             &mut self.grab_pistol,
@@ -198,7 +254,7 @@ impl ControlScheme {
         ]
     }
 
-    pub fn buttons(&self) -> [&ControlButtonDefinition; 24] {
+    pub fn buttons(&self) -> [&ControlButtonDefinition; 29] {
         [
             &self.move_forward,
             &self.move_backward,
@@ -206,14 +262,19 @@ impl ControlScheme {
             &self.move_right,
             &self.action,
             &self.drop_item,
+            &self.combine,
             &self.jump,
             &self.shoot,
+            &self.melee,
+            &self.reload,
+            &self.cycle_fire_mode,
             &self.next_weapon,
             &self.prev_weapon,
             &self.run,
             &self.aim,
             &self.inventory,
             &self.toss_grenade,
+            &self.cycle_grenade,
             &self.journal,
             &self.flash_light,
             &self.grab_pistol,
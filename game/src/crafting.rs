@@ -0,0 +1,139 @@
+//! Crafting allows to combine items in the inventory into new ones using recipes.
+
+use crate::{
+    inventory::{AddItemError, Inventory},
+    level::item::{Item, ItemKind},
+};
+use fyrox::{lazy_static::lazy_static, utils::log::Log};
+use serde::Deserialize;
+use std::{
+    fmt::{Display, Formatter},
+    fs::File,
+};
+
+#[derive(Deserialize, Debug)]
+pub struct Ingredient {
+    pub item: ItemKind,
+    pub count: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Recipe {
+    pub ingredients: Vec<Ingredient>,
+    pub result: ItemKind,
+    pub count: u32,
+}
+
+/// A reason why a recipe could not be crafted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CraftError {
+    /// There is not enough ingredients in the inventory.
+    MissingIngredients,
+    /// The result does not fit in the inventory.
+    Inventory(AddItemError),
+}
+
+impl Display for CraftError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CraftError::MissingIngredients => write!(f, "Missing ingredients"),
+            CraftError::Inventory(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Recipe {
+    pub fn uses(&self, item: ItemKind) -> bool {
+        self.ingredients.iter().any(|i| i.item == item)
+    }
+
+    pub fn has_ingredients(&self, inventory: &Inventory) -> bool {
+        self.ingredients
+            .iter()
+            .all(|i| inventory.item_count(i.item) >= i.count)
+    }
+
+    /// Replaces ingredients in the inventory with the result of the recipe. The inventory is left
+    /// untouched if anything goes wrong.
+    pub fn craft(&self, inventory: &mut Inventory) -> Result<(), CraftError> {
+        if !self.has_ingredients(inventory) {
+            return Err(CraftError::MissingIngredients);
+        }
+
+        let mut new_inventory = inventory.clone();
+
+        for ingredient in self.ingredients.iter() {
+            if new_inventory.try_extract_exact_items(ingredient.item, ingredient.count)
+                != ingredient.count
+            {
+                return Err(CraftError::MissingIngredients);
+            }
+        }
+
+        new_inventory
+            .add_item(self.result, self.count)
+            .map_err(CraftError::Inventory)?;
+
+        *inventory = new_inventory;
+
+        Ok(())
+    }
+
+    /// Returns human-readable description of the recipe, for example `Medical Gel + Bandage x2 ->
+    /// Medkit`.
+    pub fn description(&self) -> String {
+        fn item_text(item: ItemKind, count: u32) -> String {
+            let name = &Item::get_definition(item).name;
+            if count > 1 {
+                format!("{name} x{count}")
+            } else {
+                name.clone()
+            }
+        }
+
+        format!(
+            "{} -> {}",
+            self.ingredients
+                .iter()
+                .map(|i| item_text(i.item, i.count))
+                .collect::<Vec<_>>()
+                .join(" + "),
+            item_text(self.result, self.count)
+        )
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct RecipeContainer {
+    pub recipes: Vec<Recipe>,
+}
+
+impl RecipeContainer {
+    const PATH: &'static str = "data/configs/recipes.ron";
+
+    pub fn new() -> Self {
+        match File::open(Self::PATH)
+            .map_err(|e| e.to_string())
+            .and_then(|file| ron::de::from_reader(file).map_err(|e| e.to_string()))
+        {
+            Ok(container) => container,
+            Err(error) => {
+                Log::err(format!(
+                    "[Crafting]: Unable to load {}! Reason: {}",
+                    Self::PATH,
+                    error
+                ));
+                Self::default()
+            }
+        }
+    }
+
+    /// Returns every recipe that uses the given item as an ingredient.
+    pub fn recipes_using(&self, item: ItemKind) -> impl Iterator<Item = &Recipe> {
+        self.recipes.iter().filter(move |r| r.uses(item))
+    }
+}
+
+lazy_static! {
+    pub static ref RECIPES: RecipeContainer = RecipeContainer::new();
+}
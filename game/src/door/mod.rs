@@ -1,4 +1,4 @@
-use crate::{character::character_ref, current_level_mut, game_mut};
+use crate::{character::character_ref, current_level_mut, game_mut, weapon::grenade::EmpMessage};
 use fyrox::{
     core::{
         algebra::Vector3,
@@ -21,7 +21,9 @@ use fyrox::{
         node::{Node, NodeHandle, TypeUuidProvider},
         rigidbody::RigidBody,
     },
-    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+    script::{
+        ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait,
+    },
     utils::log::Log,
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
@@ -105,6 +107,11 @@ pub struct Door {
     #[visit(skip)]
     open_request: Option<OpenRequest>,
 
+    // Time left until the door recovers from EMP, disabled door can't be opened or closed.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    emp_timer: f32,
+
     #[reflect(hidden)]
     #[visit(skip)]
     self_handle: Handle<Node>,
@@ -139,6 +146,9 @@ impl ScriptTrait for Door {
             ctx.handle,
         );
         self.apply_screen_texture(&mut ctx.scene.graph, ctx.resource_manager.clone(), texture);
+
+        ctx.message_dispatcher
+            .subscribe_to::<EmpMessage>(ctx.handle);
     }
 
     fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
@@ -156,10 +166,25 @@ impl ScriptTrait for Door {
         }
     }
 
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        _ctx: &mut ScriptMessageContext,
+    ) {
+        if let Some(msg) = message.downcast_ref::<EmpMessage>() {
+            if msg.target == self.self_handle {
+                self.emp_timer = self.emp_timer.max(msg.duration);
+            }
+        }
+    }
+
     fn on_update(&mut self, ctx: &mut ScriptContext) {
         let game = game_mut(ctx.plugins);
         let level = game.level.as_ref().unwrap();
 
+        let disabled = self.emp_timer > 0.0;
+        self.emp_timer = (self.emp_timer - ctx.dt).max(0.0);
+
         let speed = 0.55;
 
         let node = &ctx.scene.graph[ctx.handle];
@@ -179,7 +204,7 @@ impl ScriptTrait for Door {
             close_enough
         });
 
-        if !someone_nearby && self.state == DoorState::Opened {
+        if !someone_nearby && self.state == DoorState::Opened && !disabled {
             self.state = DoorState::Closing;
             let position = node.global_position();
             level.sound_manager.play_sound(
@@ -207,11 +232,15 @@ impl ScriptTrait for Door {
                 DoorState::Locked => "Locked",
                 DoorState::Broken => "Broken",
             };
+            let text = if disabled { "Disabled" } else { text };
 
             ui.set_text(text.to_owned());
         }
 
         match self.state {
+            _ if disabled => {
+                self.set_lights_enabled(&mut ctx.scene.graph, false);
+            }
             DoorState::Opening => {
                 if self.offset < *self.open_offset_amount {
                     self.offset += speed * ctx.dt;
@@ -260,7 +289,16 @@ impl ScriptTrait for Door {
         if let Some(open_request) = self.open_request.take() {
             let position = self.actual_position(&ctx.scene.graph);
 
-            if self.state == DoorState::Closed {
+            if disabled {
+                level.sound_manager.play_sound(
+                    &mut ctx.scene.graph,
+                    "data/sounds/door_deny.ogg",
+                    position,
+                    1.0,
+                    1.0,
+                    1.0,
+                );
+            } else if self.state == DoorState::Closed {
                 self.state = DoorState::Opening;
 
                 level.sound_manager.play_sound(
@@ -23,6 +23,15 @@ pub struct Rail {
     radius: f32,
     particles_per_meter: f32,
     max_length: f32,
+
+    // Length of the rail set by a projectile, if it is not set the length is found by a ray cast.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    length: Option<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    needs_rebuild: bool,
 }
 
 impl Default for Rail {
@@ -31,6 +40,8 @@ impl Default for Rail {
             radius: 0.1,
             particles_per_meter: 120.0,
             max_length: 100.0,
+            length: None,
+            needs_rebuild: false,
         }
     }
 }
@@ -43,8 +54,15 @@ impl TypeUuidProvider for Rail {
     }
 }
 
-impl ScriptTrait for Rail {
-    fn on_init(&mut self, context: &mut ScriptContext) {
+impl Rail {
+    /// Sets the length of the rail explicitly. It is used by projectiles that pass through
+    /// obstacles or ricochet, so every segment of their path has its own rail.
+    pub fn set_length(&mut self, length: f32) {
+        self.length = Some(length.clamp(0.0, self.max_length));
+        self.needs_rebuild = true;
+    }
+
+    fn cast_length(&self, context: &mut ScriptContext) -> f32 {
         let node = &context.scene.graph[context.handle];
         let origin = node.global_position();
         let dir = node.look_vector();
@@ -66,9 +84,16 @@ impl ScriptTrait for Rail {
             &mut query_buffer,
         );
 
-        let len = query_buffer
+        query_buffer
             .first()
-            .map_or(self.max_length, |i| i.toi.clamp(0.0, self.max_length));
+            .map_or(self.max_length, |i| i.toi.clamp(0.0, self.max_length))
+    }
+
+    fn build(&self, context: &mut ScriptContext) {
+        let len = match self.length {
+            Some(length) => length,
+            None => self.cast_length(context),
+        };
 
         let total_particles = ((len * self.particles_per_meter) as usize).min(20000);
 
@@ -96,6 +121,19 @@ impl ScriptTrait for Rail {
             );
         }
     }
+}
+
+impl ScriptTrait for Rail {
+    fn on_init(&mut self, context: &mut ScriptContext) {
+        self.build(context);
+    }
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        if self.needs_rebuild {
+            self.needs_rebuild = false;
+            self.build(context);
+        }
+    }
 
     fn id(&self) -> Uuid {
         Self::type_uuid()
@@ -118,7 +118,9 @@ impl ScriptTrait for CallButton {
         {
             if let Some(ui) = game.call_button_ui_container.get_ui_mut(context.handle) {
                 ui.set_text(
-                    if self.floor == elevator.current_floor {
+                    if elevator.is_blocked() {
+                        "Blocked"
+                    } else if self.floor == elevator.current_floor {
                         "Ready"
                     } else if elevator.k.abs() > f32::EPSILON {
                         "Called"
@@ -129,6 +131,12 @@ impl ScriptTrait for CallButton {
                 );
 
                 ui.set_floor_text(format!("Floor {}", self.floor));
+
+                ui.set_occupancy_text(match elevator.passengers().len() {
+                    0 => "Empty".to_string(),
+                    1 => "1 person".to_string(),
+                    n => format!("{n} people"),
+                });
             }
         }
     }
@@ -1,13 +1,22 @@
-use crate::current_level_mut;
+use crate::{
+    character::{character_ref, try_get_character_ref},
+    current_level_mut, current_level_ref,
+    level::destructible::is_part_of,
+};
 use fyrox::{
     core::{
+        algebra::{Point3, Vector3},
+        pool::Handle,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
+        variable::InheritableVariable,
         visitor::prelude::*,
     },
     impl_component_provider,
     scene::{
-        node::{NodeHandle, TypeUuidProvider},
+        collider::InteractionGroups,
+        graph::{physics::RayCastOptions, Graph},
+        node::{Node, NodeHandle, TypeUuidProvider},
         rigidbody::RigidBody,
     },
     script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
@@ -16,21 +25,188 @@ use fyrox::{
 pub mod call_button;
 pub mod ui;
 
-#[derive(Visit, Reflect, Default, Debug, Clone)]
+#[derive(Visit, Reflect, Debug, Clone)]
 pub struct Elevator {
     pub current_floor: u32,
     pub dest_floor: u32,
     k: f32,
     pub point_handles: Vec<NodeHandle>,
     pub call_buttons: Vec<NodeHandle>,
+
+    #[reflect(
+        description = "Half-size of the platform along X and Z axes and the height of the space \
+        above it. Everything inside this volume is carried by the elevator. Given in local \
+        coordinates of the elevator."
+    )]
+    #[visit(optional)]
+    platform_extents: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    passengers: Vec<Handle<Node>>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    cargo: Vec<Handle<Node>>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    blocked_timer: f32,
+}
+
+impl Default for Elevator {
+    fn default() -> Self {
+        Self {
+            current_floor: 0,
+            dest_floor: 0,
+            k: 0.0,
+            point_handles: Default::default(),
+            call_buttons: Default::default(),
+            platform_extents: Vector3::new(1.0, 2.0, 1.0).into(),
+            passengers: Default::default(),
+            cargo: Default::default(),
+            blocked_timer: 0.0,
+        }
+    }
 }
 
 impl Elevator {
+    const SPEED: f32 = 0.5;
+    // Distance that must be kept free in front of the platform while it is moving.
+    const CLEARANCE: f32 = 0.05;
+    // How long call buttons should show that the elevator was blocked.
+    const BLOCKED_MESSAGE_TIME: f32 = 3.0;
+
     pub fn call_to(&mut self, floor: u32) {
         if floor < self.point_handles.len() as u32 {
             self.dest_floor = floor;
         }
     }
+
+    /// Returns handles of every actor that is currently standing on the platform.
+    pub fn passengers(&self) -> &[Handle<Node>] {
+        &self.passengers
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.current_floor != self.dest_floor
+    }
+
+    /// Returns `true` if the elevator recently had to stop because its path was blocked.
+    pub fn is_blocked(&self) -> bool {
+        self.blocked_timer > 0.0
+    }
+
+    fn is_on_platform(&self, platform: &Node, position: Vector3<f32>) -> bool {
+        let local_position = platform
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_default()
+            .transform_point(&Point3::from(position))
+            .coords;
+
+        local_position.x.abs() <= self.platform_extents.x
+            && local_position.z.abs() <= self.platform_extents.z
+            && local_position.y >= -Self::CLEARANCE
+            && local_position.y <= self.platform_extents.y
+    }
+
+    fn update_occupancy(
+        &mut self,
+        self_handle: Handle<Node>,
+        actors: &[Handle<Node>],
+        items: impl Iterator<Item = Handle<Node>>,
+        graph: &Graph,
+    ) {
+        let platform = &graph[self_handle];
+
+        self.passengers.clear();
+        for &actor in actors {
+            if let Some(character) = try_get_character_ref(actor, graph) {
+                if self.is_on_platform(platform, character.position(graph)) {
+                    self.passengers.push(actor);
+                }
+            }
+        }
+
+        self.cargo.clear();
+        for item in items {
+            if self.is_on_platform(platform, graph[item].global_position()) {
+                self.cargo.push(item);
+            }
+        }
+    }
+
+    fn is_path_blocked(
+        &self,
+        self_handle: Handle<Node>,
+        direction: Vector3<f32>,
+        distance: f32,
+        graph: &Graph,
+    ) -> bool {
+        let platform = &graph[self_handle];
+        let transform = platform.global_transform();
+
+        // Moving up: check the space above the heads of the passengers, otherwise check the space
+        // below the platform. Passengers and cargo are moving with the platform, so they are not
+        // obstacles.
+        let height = if direction.dot(&platform.up_vector()) > 0.0 {
+            self.platform_extents.y
+        } else {
+            0.0
+        };
+
+        // Check center and corners of the platform, so small obstacles won't be missed.
+        let x = self.platform_extents.x * 0.9;
+        let z = self.platform_extents.z * 0.9;
+        let probes = [
+            Vector3::new(0.0, height, 0.0),
+            Vector3::new(-x, height, -z),
+            Vector3::new(x, height, -z),
+            Vector3::new(x, height, z),
+            Vector3::new(-x, height, z),
+        ];
+
+        let passenger_colliders = self
+            .passengers
+            .iter()
+            .filter_map(|p| try_get_character_ref(*p, graph).map(|c| c.capsule_collider))
+            .collect::<Vec<_>>();
+
+        let mut query_buffer = Vec::default();
+        for probe in probes {
+            graph.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: transform.transform_point(&Point3::from(probe)),
+                    ray_direction: direction,
+                    max_len: distance + Self::CLEARANCE,
+                    groups: InteractionGroups::default(),
+                    sort_results: false,
+                },
+                &mut query_buffer,
+            );
+
+            if query_buffer.iter().any(|hit| {
+                graph[hit.collider].parent() != self_handle
+                    && !passenger_colliders.contains(&hit.collider)
+                    && !self
+                        .cargo
+                        .iter()
+                        .any(|item| is_part_of(graph, hit.collider, *item))
+            }) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn reverse(&mut self) {
+        std::mem::swap(&mut self.current_floor, &mut self.dest_floor);
+        // Keep the platform at the same place, it is just moving in the opposite direction now.
+        self.k = 1.0 - self.k;
+        self.blocked_timer = Self::BLOCKED_MESSAGE_TIME;
+    }
 }
 
 impl_component_provider!(Elevator);
@@ -58,26 +234,78 @@ impl ScriptTrait for Elevator {
     }
 
     fn on_update(&mut self, context: &mut ScriptContext) {
-        if self.current_floor != self.dest_floor {
-            self.k += 0.5 * context.dt;
+        let level = current_level_ref(context.plugins).expect("Level must exist!");
+        let graph = &mut context.scene.graph;
 
-            if self.k >= 1.0 {
-                self.current_floor = self.dest_floor;
-                self.k = 0.0;
-            }
-        }
+        self.blocked_timer -= context.dt;
+
+        self.update_occupancy(
+            context.handle,
+            &level.actors,
+            level.items.iter().cloned(),
+            graph,
+        );
 
         if let (Some(current), Some(dest)) = (
             self.point_handles.get(self.current_floor as usize),
             self.point_handles.get(self.dest_floor as usize),
         ) {
-            let current_pos = context.scene.graph[**current].global_position();
-            let dest_pos = context.scene.graph[**dest].global_position();
-            if let Some(rigid_body_ref) =
-                context.scene.graph[context.handle].cast_mut::<RigidBody>()
-            {
-                let position = current_pos.lerp(&dest_pos, self.k);
+            let current_pos = graph[**current].global_position();
+            let dest_pos = graph[**dest].global_position();
+
+            if self.is_moving() {
+                let path = dest_pos - current_pos;
+                let step = Self::SPEED * context.dt;
+
+                if let Some(direction) = path.try_normalize(f32::EPSILON) {
+                    let distance = step * path.norm();
+                    if self.is_path_blocked(context.handle, direction, distance, graph) {
+                        // Wait until the way is clear if the platform is stuck in both directions,
+                        // otherwise it would reverse every frame.
+                        if self.is_path_blocked(context.handle, -direction, distance, graph) {
+                            self.blocked_timer = Self::BLOCKED_MESSAGE_TIME;
+                        } else {
+                            self.reverse();
+                        }
+                        return;
+                    }
+                }
+
+                self.k += step;
+
+                if self.k >= 1.0 {
+                    self.current_floor = self.dest_floor;
+                    self.k = 0.0;
+                }
+            }
+
+            if let Some(rigid_body_ref) = graph[context.handle].cast_mut::<RigidBody>() {
+                let position = if self.is_moving() {
+                    current_pos.lerp(&dest_pos, self.k)
+                } else {
+                    // The floor could be changed above, in this case the destination point is
+                    // the actual position of the platform.
+                    dest_pos
+                };
+
+                let old_position = **rigid_body_ref.local_transform().position();
                 rigid_body_ref.local_transform_mut().set_position(position);
+
+                // Carry everything on the platform explicitly, relying on physics contacts only
+                // causes jittering and makes passengers fall through the platform at higher speeds.
+                let displacement = position - old_position;
+                if displacement.norm_squared() > 0.0 {
+                    for &passenger in self.passengers.iter() {
+                        let body = character_ref(passenger, graph).body;
+                        if let Some(body) = graph.try_get_mut(body) {
+                            body.local_transform_mut().offset(displacement);
+                        }
+                    }
+
+                    for &item in self.cargo.iter() {
+                        graph[item].local_transform_mut().offset(displacement);
+                    }
+                }
             }
         }
     }
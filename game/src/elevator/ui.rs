@@ -21,6 +21,7 @@ pub struct CallButtonUi {
     pub render_target: Texture,
     floor_text: Handle<UiNode>,
     text: Handle<UiNode>,
+    occupancy_text: Handle<UiNode>,
 }
 
 impl InteractiveUi for CallButtonUi {
@@ -53,6 +54,7 @@ impl CallButtonUi {
 
         let text;
         let floor_text;
+        let occupancy_text;
 
         BorderBuilder::new(
             WidgetBuilder::new()
@@ -84,14 +86,29 @@ impl CallButtonUi {
                                 )
                                 .with_text("Call?")
                                 .with_horizontal_text_alignment(HorizontalAlignment::Center)
-                                .with_font(font)
+                                .with_font(font.clone())
                                 .build(ctx);
                                 text
+                            })
+                            .with_child({
+                                occupancy_text = TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(2)
+                                        .on_column(0)
+                                        .with_margin(Thickness::bottom(10.0)),
+                                )
+                                .with_text("Empty")
+                                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .with_font(font)
+                                .build(ctx);
+                                occupancy_text
                             }),
                     )
                     .add_column(Column::stretch())
                     .add_row(Row::stretch())
                     .add_row(Row::stretch())
+                    .add_row(Row::stretch())
                     .build(ctx),
                 ),
         )
@@ -102,6 +119,7 @@ impl CallButtonUi {
             render_target,
             text,
             floor_text,
+            occupancy_text,
         }
     }
 
@@ -120,6 +138,14 @@ impl CallButtonUi {
             text,
         ));
     }
+
+    pub fn set_occupancy_text(&mut self, text: String) {
+        self.ui.send_message(TextMessage::text(
+            self.occupancy_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
 }
 
 pub type CallButtonUiContainer = UiContainer<Node, CallButtonUi>;
@@ -1,9 +1,14 @@
 use crate::{
     character::{CharacterMessage, CharacterMessageData},
     control_scheme::{ControlButton, ControlScheme},
+    crafting::{CraftError, RECIPES},
+    inventory::Inventory,
     level::item::{Item, ItemKind},
     message::Message,
-    player::Player,
+    player::{
+        equipment::{Equipment, EquipmentDefinition},
+        Player,
+    },
     MessageSender,
 };
 use fyrox::{
@@ -32,6 +37,7 @@ use fyrox::{
 use std::{
     any::{Any, TypeId},
     ops::{Deref, DerefMut},
+    path::PathBuf,
 };
 
 pub struct InventoryInterface {
@@ -42,6 +48,13 @@ pub struct InventoryInterface {
     sender: MessageSender,
     item_description: Handle<UiNode>,
     scroll_viewer: Handle<UiNode>,
+    header: Handle<UiNode>,
+    // A second pane that shows contents of an opened container.
+    container_pane: Handle<UiNode>,
+    container_panel: Handle<UiNode>,
+    container_scroll_viewer: Handle<UiNode>,
+    container_header: Handle<UiNode>,
+    equipment_text: Handle<UiNode>,
 }
 
 #[derive(Debug, Clone)]
@@ -49,6 +62,8 @@ pub struct InventoryItem {
     widget: Widget,
     is_selected: bool,
     item: ItemKind,
+    // Amount of items in the stack.
+    amount: u32,
     #[allow(dead_code)]
     count: Handle<UiNode>,
 }
@@ -201,6 +216,7 @@ impl InventoryItemBuilder {
             count,
             is_selected: false,
             item,
+            amount: self.count as u32,
         };
 
         ctx.add_node(UiNode::new(item))
@@ -217,6 +233,9 @@ enum MoveDirection {
 impl InventoryInterface {
     pub const WIDTH: f32 = 400.0;
     pub const HEIGHT: f32 = 300.0;
+    // Size of a single slot of the inventory grid (including margins).
+    const SLOT_WIDTH: f32 = 40.0;
+    const SLOT_HEIGHT: f32 = 64.0;
 
     pub fn new(sender: MessageSender) -> Self {
         let mut ui = UserInterface::new(Vector2::new(Self::WIDTH, Self::HEIGHT));
@@ -226,6 +245,43 @@ impl InventoryInterface {
         let items_panel;
         let item_description;
         let scroll_viewer;
+        let header;
+
+        let ctx = &mut ui.build_ctx();
+        let container_panel = Self::make_items_panel(ctx);
+        let container_header = TextBuilder::new(WidgetBuilder::new())
+            .with_text("Container")
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .build(ctx);
+        let container_scroll_viewer =
+            ScrollViewerBuilder::new(WidgetBuilder::new().with_height(2.0 * Self::SLOT_HEIGHT))
+                .with_content(container_panel)
+                .build(ctx);
+        let equipment_text = TextBuilder::new(WidgetBuilder::new())
+            .with_wrap(WrapMode::Word)
+            .build(ctx);
+        let equipment_panel = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_text("Equipment")
+                        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                        .build(ctx),
+                )
+                .with_child(equipment_text),
+        )
+        .build(ctx);
+        // Hidden until a container is opened.
+        let container_pane = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_visibility(false)
+                .with_child(container_header)
+                .with_child(container_scroll_viewer),
+        )
+        .build(ctx);
+
         BorderBuilder::new(
             WidgetBuilder::new()
                 .with_foreground(Brush::Solid(Color::opaque(120, 120, 120)))
@@ -235,37 +291,39 @@ impl InventoryInterface {
                 .with_child(
                     GridBuilder::new(
                         WidgetBuilder::new()
-                            .with_child(
-                                TextBuilder::new(WidgetBuilder::new().on_row(0))
+                            .with_child({
+                                header = TextBuilder::new(WidgetBuilder::new().on_row(0))
                                     .with_text("Inventory")
                                     .with_horizontal_text_alignment(HorizontalAlignment::Center)
-                                    .build(&mut ui.build_ctx()),
-                            )
+                                    .build(&mut ui.build_ctx());
+                                header
+                            })
                             .with_child(
                                 GridBuilder::new(
                                     WidgetBuilder::new()
                                         .on_row(1)
-                                        .with_child({
-                                            scroll_viewer =
-                                                ScrollViewerBuilder::new(WidgetBuilder::new())
-                                                    .with_content({
-                                                        items_panel = WrapPanelBuilder::new(
-                                                            WidgetBuilder::new()
-                                                                .with_horizontal_alignment(
-                                                                    HorizontalAlignment::Left,
-                                                                )
-                                                                .with_vertical_alignment(
-                                                                    VerticalAlignment::Top,
-                                                                )
-                                                                .on_column(0),
+                                        .with_child(
+                                            GridBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .on_column(0)
+                                                    .with_child({
+                                                        items_panel = Self::make_items_panel(
+                                                            &mut ui.build_ctx(),
+                                                        );
+                                                        scroll_viewer = ScrollViewerBuilder::new(
+                                                            WidgetBuilder::new().on_row(0),
                                                         )
-                                                        .with_orientation(Orientation::Horizontal)
+                                                        .with_content(items_panel)
                                                         .build(&mut ui.build_ctx());
-                                                        items_panel
+                                                        scroll_viewer
                                                     })
-                                                    .build(&mut ui.build_ctx());
-                                            scroll_viewer
-                                        })
+                                                    .with_child(container_pane),
+                                            )
+                                            .add_row(Row::stretch())
+                                            .add_row(Row::auto())
+                                            .add_column(Column::stretch())
+                                            .build(&mut ui.build_ctx()),
+                                        )
                                         .with_child(
                                             BorderBuilder::new(
                                                 WidgetBuilder::new()
@@ -297,7 +355,8 @@ impl InventoryInterface {
                                                                 }),
                                                         )
                                                         .build(&mut ui.build_ctx()),
-                                                    ),
+                                                    )
+                                                    .with_child(equipment_panel),
                                             )
                                             .build(&mut ui.build_ctx()),
                                         ),
@@ -324,37 +383,179 @@ impl InventoryInterface {
             sender,
             item_description,
             scroll_viewer,
+            header,
+            container_pane,
+            container_panel,
+            container_scroll_viewer,
+            container_header,
+            equipment_text,
+        }
+    }
+
+    fn make_items_panel(ctx: &mut BuildContext) -> Handle<UiNode> {
+        // Items are laid out in a fixed grid.
+        WrapPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(Inventory::COLUMNS as f32 * Self::SLOT_WIDTH)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx)
+    }
+
+    pub fn sync_to_model(
+        &mut self,
+        resource_manager: ResourceManager,
+        player: &Player,
+        container: Option<&Inventory>,
+    ) {
+        let inventory = player.inventory();
+
+        self.fill_items_panel(self.items_panel, inventory, resource_manager.clone());
+
+        self.ui.send_message(TextMessage::text(
+            self.header,
+            MessageDirection::ToWidget,
+            format!(
+                "Inventory - {}/{} slots, {:.1}/{:.1} kg",
+                inventory.used_slots(),
+                inventory.capacity(),
+                inventory.weight(),
+                Inventory::MAX_WEIGHT
+            ),
+        ));
+
+        self.ui.send_message(WidgetMessage::visibility(
+            self.container_pane,
+            MessageDirection::ToWidget,
+            container.is_some(),
+        ));
+
+        match container {
+            Some(container) => {
+                self.fill_items_panel(self.container_panel, container, resource_manager);
+
+                self.ui.send_message(TextMessage::text(
+                    self.container_header,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Container - {}/{} slots",
+                        container.used_slots(),
+                        container.capacity()
+                    ),
+                ));
+            }
+            None => self.clear_items_panel(self.container_panel),
         }
     }
 
-    pub fn sync_to_model(&mut self, resource_manager: ResourceManager, player: &Player) {
-        for &child in self.ui.node(self.items_panel).children() {
+    /// Shows state of the equipment of the player, it changes over time so it is synced every
+    /// frame.
+    pub fn sync_equipment(&mut self, equipment: &Equipment, definition: &EquipmentDefinition) {
+        let mut text = format!(
+            "Battery: {:.0}%\nFlash light: {}\nModules {}/{}",
+            equipment.battery() * 100.0,
+            if equipment.is_flash_light_enabled() {
+                "On"
+            } else {
+                "Off"
+            },
+            equipment.modules().len(),
+            definition.max_modules
+        );
+        for module in equipment.modules() {
+            text += &format!("\n- {}", module.as_ref());
+        }
+
+        self.ui.send_message(TextMessage::text(
+            self.equipment_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn clear_items_panel(&mut self, panel: Handle<UiNode>) {
+        for &child in self.ui.node(panel).children() {
             self.ui
                 .send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
         }
+    }
+
+    fn fill_items_panel(
+        &mut self,
+        panel: Handle<UiNode>,
+        inventory: &Inventory,
+        resource_manager: ResourceManager,
+    ) {
+        self.clear_items_panel(panel);
+
+        // Every stack of items gets its own cell that spans `slot_size` slots.
+        for item in inventory.items() {
+            let slot_size = Item::get_definition(item.kind())
+                .slot_size
+                .clamp(1, Inventory::COLUMNS);
+
+            for amount in Inventory::stacks(item) {
+                let ctx = &mut self.ui.build_ctx();
+
+                let widget = InventoryItemBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(1.0))
+                        .with_width(slot_size as f32 * Self::SLOT_WIDTH - 2.0)
+                        .with_height(Self::SLOT_HEIGHT - 2.0),
+                )
+                .with_count(amount as usize)
+                .build(item.kind(), resource_manager.clone(), ctx);
+
+                self.ui.send_message(WidgetMessage::link(
+                    widget,
+                    MessageDirection::ToWidget,
+                    panel,
+                ));
+            }
+        }
 
-        for item in player.inventory().items() {
+        // Fill the rest of the grid with empty slots.
+        for _ in inventory.used_slots()..inventory.capacity() {
             let ctx = &mut self.ui.build_ctx();
 
-            let widget = InventoryItemBuilder::new(
+            let empty_slot = BorderBuilder::new(
                 WidgetBuilder::new()
                     .with_margin(Thickness::uniform(1.0))
-                    .with_width(70.0)
-                    .with_height(100.0),
+                    .with_width(Self::SLOT_WIDTH - 2.0)
+                    .with_height(Self::SLOT_HEIGHT - 2.0)
+                    .with_foreground(Brush::Solid(Color::opaque(60, 60, 60))),
             )
-            .with_count(item.amount() as usize)
-            .build(item.kind(), resource_manager.clone(), ctx);
+            .build(ctx);
 
             self.ui.send_message(WidgetMessage::link(
-                widget,
+                empty_slot,
                 MessageDirection::ToWidget,
-                self.items_panel,
+                panel,
             ));
         }
     }
 
+    /// Returns handles of items of both panes, the container pane is empty if there's no opened
+    /// container.
+    fn item_handles(&self) -> Vec<Handle<UiNode>> {
+        self.ui
+            .node(self.items_panel)
+            .children()
+            .iter()
+            .chain(self.ui.node(self.container_panel).children())
+            .cloned()
+            .filter(|&c| self.ui.node(c).cast::<InventoryItem>().is_some())
+            .collect()
+    }
+
+    fn is_in_container_pane(&self, item_handle: Handle<UiNode>) -> bool {
+        self.ui.node(item_handle).parent() == self.container_panel
+    }
+
     pub fn selection(&self) -> Handle<UiNode> {
-        for &item_handle in self.ui.node(self.items_panel).children() {
+        for item_handle in self.item_handles() {
             if let Some(inventory_item) = self.ui.node(item_handle).cast::<InventoryItem>() {
                 if inventory_item.is_selected {
                     return item_handle;
@@ -365,7 +566,8 @@ impl InventoryInterface {
     }
 
     fn try_move_selection(&mut self, dir: MoveDirection) {
-        let items = self.ui.node(self.items_panel).children();
+        // Empty slots could not be selected, the selection could move between the panes.
+        let items = self.item_handles();
 
         let mut direction = match dir {
             MoveDirection::Up => -Vector2::y(),
@@ -391,7 +593,7 @@ impl InventoryInterface {
             let mut closest = Handle::NONE;
             let mut closest_distance = std::f32::MAX;
 
-            for &item_handle in items {
+            for item_handle in items {
                 let item_bounds = self.ui.node(item_handle).screen_bounds();
 
                 if let Some(intersection) =
@@ -412,7 +614,11 @@ impl InventoryInterface {
                 ));
 
                 self.ui.send_message(ScrollViewerMessage::bring_into_view(
-                    self.scroll_viewer,
+                    if self.is_in_container_pane(closest) {
+                        self.container_scroll_viewer
+                    } else {
+                        self.scroll_viewer
+                    },
                     MessageDirection::ToWidget,
                     closest,
                 ));
@@ -420,6 +626,39 @@ impl InventoryInterface {
         }
     }
 
+    /// Crafts the first recipe that uses the given item and has every ingredient in the
+    /// inventory of the player.
+    fn combine(&self, item: ItemKind, player: &mut Player) {
+        let result = match RECIPES
+            .recipes_using(item)
+            .find(|r| r.has_ingredients(player.inventory()))
+        {
+            Some(recipe) => recipe.craft(player.inventory_mut()),
+            None => Err(CraftError::MissingIngredients),
+        };
+
+        match result {
+            Ok(()) => {
+                self.sender.send(Message::Play2DSound {
+                    path: PathBuf::from("data/sounds/item_pickup.ogg"),
+                    gain: 1.0,
+                });
+                self.sender.send(Message::SyncInventory);
+            }
+            Err(error) => {
+                self.sender.send(Message::Play2DSound {
+                    path: PathBuf::from("data/sounds/door_deny.ogg"),
+                    gain: 1.0,
+                });
+                self.ui.send_message(TextMessage::text(
+                    self.item_description,
+                    MessageDirection::ToWidget,
+                    format!("Unable to combine: {error}"),
+                ));
+            }
+        }
+    }
+
     pub fn process_os_event(
         &mut self,
         os_event: &OsEvent,
@@ -460,11 +699,41 @@ impl InventoryInterface {
                                 if let Some(item) = self.ui.node(selection).cast::<InventoryItem>()
                                 {
                                     let definition = Item::get_definition(item.item);
-                                    if definition.consumable {
-                                        if player
-                                            .inventory_mut()
-                                            .try_extract_exact_items(item.item, 1)
-                                            == 1
+                                    let container = player.opened_container();
+                                    if container.is_some() {
+                                        // Stacks are moved between the inventory and the opened
+                                        // container as a whole.
+                                        let (item, count) = (item.item, item.amount);
+                                        player
+                                            .script_message_sender
+                                            .as_ref()
+                                            .unwrap()
+                                            .send_to_target(
+                                                player_handle,
+                                                CharacterMessage {
+                                                    character: player_handle,
+                                                    data: if self.is_in_container_pane(selection) {
+                                                        CharacterMessageData::TakeFromContainer {
+                                                            container,
+                                                            item,
+                                                            count,
+                                                        }
+                                                    } else {
+                                                        CharacterMessageData::PutToContainer {
+                                                            container,
+                                                            item,
+                                                            count,
+                                                        }
+                                                    },
+                                                },
+                                            );
+                                        self.sender.send(Message::SyncInventory);
+                                    } else if definition.consumable {
+                                        if player.can_use_item(item.item)
+                                            && player
+                                                .inventory_mut()
+                                                .try_extract_exact_items(item.item, 1)
+                                                == 1
                                         {
                                             player.use_item(item.item);
                                             self.sender.send(Message::SyncInventory);
@@ -492,10 +761,25 @@ impl InventoryInterface {
                             }
                         }
                     }
+                    if let ControlButton::Key(key) = control_scheme.combine.button {
+                        if fyrox::utils::translate_key(key) == button {
+                            let selection = self.selection();
+                            // Only items of the inventory could be combined.
+                            if selection.is_some() && !self.is_in_container_pane(selection) {
+                                if let Some(item) = self.ui.node(selection).cast::<InventoryItem>()
+                                {
+                                    self.combine(item.item, player);
+                                } else {
+                                    unreachable!()
+                                }
+                            }
+                        }
+                    }
                     if let ControlButton::Key(key) = control_scheme.drop_item.button {
                         if fyrox::utils::translate_key(key) == button {
                             let selection = self.selection();
-                            if selection.is_some() {
+                            // Only items of the inventory could be dropped.
+                            if selection.is_some() && !self.is_in_container_pane(selection) {
                                 if let Some(item) = self.ui.node(selection).cast::<InventoryItem>()
                                 {
                                     player
@@ -506,9 +790,10 @@ impl InventoryInterface {
                                             player_handle,
                                             CharacterMessage {
                                                 character: player_handle,
+                                                // The whole stack is dropped as a single item.
                                                 data: CharacterMessageData::DropItems {
                                                     item: item.item,
-                                                    count: 1,
+                                                    count: item.amount,
                                                 },
                                             },
                                         );
@@ -533,7 +818,7 @@ impl InventoryInterface {
                         let definition = Item::get_definition(item.item);
 
                         // Deselect every other item.
-                        for &item_handle in self.ui.node(self.items_panel).children() {
+                        for item_handle in self.item_handles() {
                             if item_handle != message.destination() {
                                 self.ui.send_message(InventoryItemMessage::select(
                                     item_handle,
@@ -543,10 +828,23 @@ impl InventoryInterface {
                             }
                         }
 
+                        let recipes = RECIPES
+                            .recipes_using(item.item)
+                            .map(|r| r.description())
+                            .collect::<Vec<_>>();
+
                         self.ui.send_message(TextMessage::text(
                             self.item_description,
                             MessageDirection::ToWidget,
-                            definition.description.clone(),
+                            if recipes.is_empty() {
+                                definition.description.clone()
+                            } else {
+                                format!(
+                                    "{}\n\nCombine:\n{}",
+                                    definition.description,
+                                    recipes.join("\n")
+                                )
+                            },
                         ));
                     } else {
                         unreachable!();
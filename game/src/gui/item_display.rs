@@ -1,4 +1,7 @@
-use crate::level::item::{Item, ItemKind};
+use crate::{
+    inventory::AddItemError,
+    level::item::{Item, ItemKind},
+};
 use fyrox::{
     core::{algebra::Vector2, color::Color, pool::Handle},
     engine::resource_manager::ResourceManager,
@@ -21,6 +24,7 @@ pub struct ItemDisplay {
     pub render_target: Texture,
     item_image: Handle<UiNode>,
     item_name: Handle<UiNode>,
+    status: Handle<UiNode>,
 }
 
 impl ItemDisplay {
@@ -34,6 +38,7 @@ impl ItemDisplay {
 
         let item_image;
         let item_name;
+        let status;
         GridBuilder::new(
             WidgetBuilder::new()
                 .with_width(Self::WIDTH)
@@ -67,9 +72,23 @@ impl ItemDisplay {
                             .on_row(1)
                             .on_column(0),
                     )
-                    .with_font(font)
+                    .with_font(font.clone())
                     .build(&mut ui.build_ctx());
                     item_name
+                })
+                .with_child({
+                    // Drawn over the preview, so the size of the display stays the same.
+                    status = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_foreground(Brush::Solid(Color::opaque(255, 60, 60)))
+                            .with_vertical_alignment(VerticalAlignment::Bottom)
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .on_row(0)
+                            .on_column(0),
+                    )
+                    .with_font(font)
+                    .build(&mut ui.build_ctx());
+                    status
                 }),
         )
         .add_column(Column::auto())
@@ -82,10 +101,18 @@ impl ItemDisplay {
             render_target,
             item_image,
             item_name,
+            status,
         }
     }
 
-    pub fn sync_to_model(&self, resource_manager: ResourceManager, item: ItemKind, count: u32) {
+    /// Shows the item and a reason why it could not be picked up, if any.
+    pub fn sync_to_model(
+        &self,
+        resource_manager: ResourceManager,
+        item: ItemKind,
+        count: u32,
+        error: Option<AddItemError>,
+    ) {
         let definition = Item::get_definition(item);
 
         self.ui.send_message(TextMessage::text(
@@ -94,6 +121,12 @@ impl ItemDisplay {
             format!("{}-{}", definition.name, count),
         ));
 
+        self.ui.send_message(TextMessage::text(
+            self.status,
+            MessageDirection::ToWidget,
+            error.map(|e| e.to_string()).unwrap_or_default(),
+        ));
+
         self.ui.send_message(ImageMessage::texture(
             self.item_image,
             MessageDirection::ToWidget,
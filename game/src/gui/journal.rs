@@ -29,6 +29,7 @@ use std::{collections::HashMap, fs::File};
 #[repr(u32)]
 pub enum JournalEntryKind {
     CurrentSituation,
+    ResearchNotes,
 }
 
 impl Default for JournalEntryKind {
@@ -77,6 +78,16 @@ impl Journal {
             messages: vec![JournalEntryKind::CurrentSituation],
         }
     }
+
+    /// Adds a new entry to the journal. Returns `false` if the entry was already there.
+    pub fn add_entry(&mut self, entry: JournalEntryKind) -> bool {
+        if self.messages.contains(&entry) {
+            false
+        } else {
+            self.messages.push(entry);
+            true
+        }
+    }
 }
 
 pub struct JournalDisplay {
@@ -1,4 +1,8 @@
-use crate::{level::item::ItemKind, player::Player, weapon::try_weapon_ref};
+use crate::{
+    level::item::ItemKind,
+    player::Player,
+    weapon::{try_weapon_ref, upgrade::UpgradeStat},
+};
 use fyrox::{
     core::{algebra::Vector2, color::Color, pool::Handle},
     engine::resource_manager::ResourceManager,
@@ -23,6 +27,7 @@ pub struct WeaponDisplay {
     pub render_target: Texture,
     ammo: Handle<UiNode>,
     grenades: Handle<UiNode>,
+    status_effects: Handle<UiNode>,
 }
 
 impl WeaponDisplay {
@@ -36,6 +41,7 @@ impl WeaponDisplay {
 
         let ammo;
         let grenades;
+        let status_effects;
         GridBuilder::new(
             WidgetBuilder::new()
                 .with_width(Self::WIDTH)
@@ -86,9 +92,20 @@ impl WeaponDisplay {
                             .on_row(1)
                             .on_column(1),
                     )
-                    .with_font(font)
+                    .with_font(font.clone())
                     .build(&mut ui.build_ctx());
                     grenades
+                })
+                .with_child({
+                    status_effects = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_foreground(Brush::Solid(Color::opaque(232, 60, 0)))
+                            .on_row(2)
+                            .on_column(0),
+                    )
+                    .with_font(font)
+                    .build(&mut ui.build_ctx());
+                    status_effects
                 }),
         )
         .add_column(Column::auto())
@@ -103,27 +120,71 @@ impl WeaponDisplay {
             render_target,
             ammo,
             grenades,
+            status_effects,
         }
     }
 
     pub fn sync_to_model(&self, player: &Player, graph: &Graph) {
         let ammo = if let Some(weapon) = try_weapon_ref(player.current_weapon(), graph) {
-            let total_ammo = player.inventory().item_count(ItemKind::Ammo);
-            total_ammo / *weapon.ammo_consumption_per_shot
+            // Both the magazine and the reserve are shown in shots.
+            let ammo_per_shot = (*weapon.ammo_consumption_per_shot).max(1);
+            let reserve = player.inventory().item_count(ItemKind::Ammo);
+            let mut text = format!(
+                "{}/{} {}",
+                weapon.loaded_ammo() / ammo_per_shot,
+                reserve / ammo_per_shot,
+                weapon.fire_mode().as_ref()
+            );
+            // Upgrade levels are shown by the first letter of each stat, for example "D2 M1".
+            let upgrades = UpgradeStat::ALL
+                .iter()
+                .filter(|&&stat| weapon.upgrades().level(stat) > 0)
+                .map(|&stat| format!("{}{}", &stat.as_ref()[..1], weapon.upgrades().level(stat)))
+                .collect::<Vec<_>>();
+            if !upgrades.is_empty() {
+                text.push('\n');
+                text.push_str(&upgrades.join(" "));
+            }
+            text
         } else {
-            0
+            "0/0".to_string()
         };
         self.ui.send_message(TextMessage::text(
             self.ammo,
             MessageDirection::ToWidget,
-            format!("{ammo}"),
+            ammo,
         ));
 
-        let grenades = player.inventory().item_count(ItemKind::Grenade);
+        let grenade = player.selected_grenade();
+        let grenades = player.inventory().item_count(grenade);
+        let grenade_name = match grenade {
+            ItemKind::StasisGrenade => "Stasis",
+            ItemKind::IncendiaryGrenade => "Incendiary",
+            ItemKind::EmpGrenade => "EMP",
+            _ => "Frag",
+        };
         self.ui.send_message(TextMessage::text(
             self.grenades,
             MessageDirection::ToWidget,
-            format!("{grenades}"),
+            format!("{grenades} {grenade_name}"),
+        ));
+
+        let status_effects = player
+            .status_effects
+            .iter()
+            .map(|effect| {
+                if effect.stacks() > 1 {
+                    format!("{} x{}", effect.kind().as_ref(), effect.stacks())
+                } else {
+                    effect.kind().as_ref().to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.ui.send_message(TextMessage::text(
+            self.status_effects,
+            MessageDirection::ToWidget,
+            status_effects,
         ));
     }
 
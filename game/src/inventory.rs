@@ -1,5 +1,6 @@
-use crate::level::item::ItemKind;
+use crate::level::item::{Item, ItemKind};
 use fyrox::core::{reflect::prelude::*, visitor::prelude::*};
+use std::fmt::{Display, Formatter};
 
 #[derive(Default, Debug, Clone, Visit, Reflect)]
 pub struct ItemEntry {
@@ -17,22 +18,109 @@ impl ItemEntry {
     }
 }
 
+/// A reason why items were not added to an inventory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AddItemError {
+    /// There is not enough free slots for the items.
+    NoRoom,
+    /// Total weight of the items in the inventory would exceed the limit.
+    TooHeavy,
+}
+
+impl Display for AddItemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddItemError::NoRoom => write!(f, "No room"),
+            AddItemError::TooHeavy => write!(f, "Too heavy"),
+        }
+    }
+}
+
+/// Items are stored in stacks, every stack occupies `slot_size` slots of a fixed grid and could
+/// hold up to `max_stack` items of the same kind (see item definitions).
 #[derive(Default, Clone, Visit, Reflect, Debug)]
 pub struct Inventory {
     items: Vec<ItemEntry>,
+    // Rows added to the grid by equipment.
+    #[visit(optional)]
+    extra_rows: u32,
 }
 
 impl Inventory {
+    pub const COLUMNS: u32 = 6;
+    pub const ROWS: u32 = 4;
+    /// Maximum total weight (in kilograms) of items.
+    pub const MAX_WEIGHT: f32 = 40.0;
+
     pub fn new() -> Self {
-        Self { items: vec![] }
+        Self {
+            items: vec![],
+            extra_rows: 0,
+        }
     }
 
     pub fn from_inner(items: Vec<ItemEntry>) -> Self {
-        Self { items }
+        Self {
+            items,
+            extra_rows: 0,
+        }
     }
 
-    pub fn add_item(&mut self, item: ItemKind, count: u32) {
-        assert_ne!(count, 0);
+    /// Returns total amount of slots in the grid.
+    pub fn capacity(&self) -> u32 {
+        Self::COLUMNS * (Self::ROWS + self.extra_rows)
+    }
+
+    pub fn set_extra_rows(&mut self, extra_rows: u32) {
+        self.extra_rows = extra_rows;
+    }
+
+    /// Checks whether the given amount of items could be added to the inventory.
+    pub fn can_add_item(&self, item: ItemKind, count: u32) -> Result<(), AddItemError> {
+        let definition = Item::get_definition(item);
+
+        let new_amount = self.item_count(item) + count;
+        let new_slots = self.used_slots() - self.slots_of(item, self.item_count(item))
+            + self.slots_of(item, new_amount);
+        if new_slots > self.capacity() {
+            return Err(AddItemError::NoRoom);
+        }
+
+        if self.weight() + definition.weight * count as f32 > Self::MAX_WEIGHT {
+            return Err(AddItemError::TooHeavy);
+        }
+
+        Ok(())
+    }
+
+    /// Returns maximum amount of items of the given kind that could be added to the inventory.
+    pub fn max_addable(&self, item: ItemKind) -> u32 {
+        let definition = Item::get_definition(item);
+        let max_stack = definition.max_stack.max(1);
+
+        let amount = self.item_count(item);
+        let free_slots = self.capacity().saturating_sub(self.used_slots());
+        let stacks =
+            (amount + max_stack - 1) / max_stack + free_slots / definition.slot_size.max(1);
+        let by_room = (stacks * max_stack).saturating_sub(amount);
+
+        if definition.weight > 0.0 {
+            let by_weight =
+                ((Self::MAX_WEIGHT - self.weight()) / definition.weight).max(0.0) as u32;
+            by_room.min(by_weight)
+        } else {
+            by_room
+        }
+    }
+
+    /// Adds the given amount of items to the inventory. Nothing is added if all the items do not
+    /// fit in.
+    pub fn add_item(&mut self, item: ItemKind, count: u32) -> Result<(), AddItemError> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        self.can_add_item(item, count)?;
 
         if let Some(item) = self.entry_mut(item) {
             item.amount += count;
@@ -42,6 +130,36 @@ impl Inventory {
                 amount: count,
             })
         }
+
+        Ok(())
+    }
+
+    /// Returns amounts of items in every stack of the given entry.
+    pub fn stacks(entry: &ItemEntry) -> impl Iterator<Item = u32> {
+        let max_stack = Item::get_definition(entry.kind).max_stack.max(1);
+        let amount = entry.amount;
+        (0..(amount + max_stack - 1) / max_stack)
+            .map(move |i| (amount - i * max_stack).min(max_stack))
+    }
+
+    fn slots_of(&self, item: ItemKind, amount: u32) -> u32 {
+        let definition = Item::get_definition(item);
+        let max_stack = definition.max_stack.max(1);
+        (amount + max_stack - 1) / max_stack * definition.slot_size
+    }
+
+    pub fn used_slots(&self) -> u32 {
+        self.items
+            .iter()
+            .map(|i| self.slots_of(i.kind, i.amount))
+            .sum()
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.items
+            .iter()
+            .map(|i| Item::get_definition(i.kind).weight * i.amount as f32)
+            .sum()
     }
 
     pub fn try_extract_exact_items(&mut self, item: ItemKind, amount: u32) -> u32 {
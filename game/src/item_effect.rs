@@ -0,0 +1,90 @@
+//! Effects of usable items. Every item lists its effects in `data/configs/items.ron`, so new
+//! consumables could be added without touching the code that uses them.
+
+use crate::{
+    character::{Character, StaminaDefinition},
+    gui::journal::{Journal, JournalEntryKind},
+    level::item::{Item, ItemKind},
+    player::equipment::{Equipment, EquipmentDefinition, SuitModule},
+    status_effect::StatusEffectKind,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub enum ItemEffect {
+    /// Restores given amount of health.
+    Heal(f32),
+    /// Applies a status effect to the user of the item.
+    ApplyStatusEffect(StatusEffectKind),
+    /// Restores given amount of stamina.
+    RestoreStamina(f32),
+    /// Restores given amount of armor.
+    GrantArmor(f32),
+    /// Adds an entry to the journal, only the player has a journal.
+    UnlockJournalEntry(JournalEntryKind),
+    /// Adds given amount of charge to the battery of the suit, only the player has a suit.
+    ChargeBattery(f32),
+    /// Installs a module into the suit.
+    InstallModule(SuitModule),
+}
+
+/// Executes effects of items on characters that use them.
+pub struct ItemEffectRegistry;
+
+impl ItemEffectRegistry {
+    pub fn effects(item: ItemKind) -> &'static [ItemEffect] {
+        &Item::get_definition(item).effects
+    }
+
+    /// Returns `false` if the item has an effect on the equipment that could not be applied right
+    /// now, for example when the battery is full or the module is already installed.
+    pub fn can_apply(
+        item: ItemKind,
+        equipment: Option<(&Equipment, &EquipmentDefinition)>,
+    ) -> bool {
+        Self::effects(item).iter().all(|effect| match *effect {
+            ItemEffect::ChargeBattery(_) => {
+                equipment.map_or(false, |(equipment, _)| equipment.can_charge_battery())
+            }
+            ItemEffect::InstallModule(module) => equipment
+                .map_or(false, |(equipment, definition)| {
+                    equipment.can_install_module(module, definition)
+                }),
+            _ => true,
+        })
+    }
+
+    /// Applies every effect of the item to the character. The journal and the equipment are
+    /// optional, because only the player has them.
+    pub fn apply(
+        item: ItemKind,
+        character: &mut Character,
+        stamina: &StaminaDefinition,
+        mut journal: Option<&mut Journal>,
+        mut equipment: Option<(&mut Equipment, &EquipmentDefinition)>,
+    ) {
+        for effect in Self::effects(item) {
+            match *effect {
+                ItemEffect::Heal(amount) => character.heal(amount),
+                ItemEffect::ApplyStatusEffect(kind) => character.status_effects.apply(kind),
+                ItemEffect::RestoreStamina(amount) => character.restore_stamina(amount, stamina),
+                ItemEffect::GrantArmor(amount) => character.repair_armor(amount),
+                ItemEffect::UnlockJournalEntry(entry) => {
+                    if let Some(journal) = journal.as_deref_mut() {
+                        journal.add_entry(entry);
+                    }
+                }
+                ItemEffect::ChargeBattery(amount) => {
+                    if let Some((equipment, definition)) = equipment.as_mut() {
+                        equipment.charge_battery(amount, definition);
+                    }
+                }
+                ItemEffect::InstallModule(module) => {
+                    if let Some((equipment, definition)) = equipment.as_mut() {
+                        equipment.install_module(module, definition);
+                    }
+                }
+            }
+        }
+    }
+}
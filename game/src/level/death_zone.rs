@@ -2,6 +2,7 @@ use crate::character::DamageDealer;
 use crate::{
     character::{CharacterMessage, CharacterMessageData},
     current_level_ref,
+    weapon::projectile::DamageType,
 };
 use fyrox::{
     core::{
@@ -39,6 +40,7 @@ impl ScriptTrait for DeathZone {
                         },
                         hitbox: None,
                         amount: 99999.0,
+                        kind: DamageType::Crush,
                         critical_hit_probability: 0.0,
                         position: None,
                     },
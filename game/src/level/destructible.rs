@@ -0,0 +1,143 @@
+//! Destructible props - crates, barrels, panels, etc. that could be broken by explosions.
+
+use crate::{current_level_mut, game_ref, level::item::Item, loot::LOOT_TABLES};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    impl_component_provider,
+    resource::model::Model,
+    scene::{
+        graph::Graph,
+        node::{Node, TypeUuidProvider},
+    },
+    script::{
+        ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait,
+    },
+};
+
+/// Applies damage to any non-character entity registered in [`crate::level::Level::destructibles`].
+pub struct DestructibleMessage {
+    pub destructible: Handle<Node>,
+    pub amount: f32,
+}
+
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Destructible {
+    #[visit(optional)]
+    health: f32,
+
+    #[visit(optional)]
+    #[reflect(
+        description = "A prefab that will be instantiated when the object is destroyed. Usually it is debris or an explosion."
+    )]
+    destroy_effect: Option<Model>,
+
+    #[visit(optional)]
+    #[reflect(
+        description = "A name of the loot table (see data/configs/loot.ron) that is used to generate items that drop out when the object is destroyed. Empty means no loot."
+    )]
+    loot_table: String,
+}
+
+impl Default for Destructible {
+    fn default() -> Self {
+        Self {
+            health: 50.0,
+            destroy_effect: None,
+            loot_table: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(Destructible);
+
+impl TypeUuidProvider for Destructible {
+    fn type_uuid() -> Uuid {
+        uuid!("cf47f14d-b2e7-4eca-814e-7cf3eb4ec1cb")
+    }
+}
+
+impl ScriptTrait for Destructible {
+    fn on_init(&mut self, ctx: &mut ScriptContext) {
+        current_level_mut(ctx.plugins)
+            .unwrap()
+            .destructibles
+            .push(ctx.handle);
+    }
+
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        ctx.message_dispatcher
+            .subscribe_to::<DestructibleMessage>(ctx.handle);
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        if let Some(level) = current_level_mut(ctx.plugins) {
+            if let Some(position) = level
+                .destructibles
+                .iter()
+                .position(|h| *h == ctx.node_handle)
+            {
+                level.destructibles.remove(position);
+            }
+        }
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        if let Some(msg) = message.downcast_ref::<DestructibleMessage>() {
+            if msg.destructible != ctx.handle || self.health <= 0.0 {
+                return;
+            }
+
+            self.health -= msg.amount;
+
+            if self.health <= 0.0 {
+                let position = ctx.scene.graph[ctx.handle].global_position();
+
+                if let Some(effect) = self.destroy_effect.as_ref() {
+                    effect.instantiate_at(ctx.scene, position, UnitQuaternion::default());
+                }
+
+                ctx.scene.graph.remove_node(ctx.handle);
+
+                if !self.loot_table.is_empty() {
+                    for entry in
+                        LOOT_TABLES.generate(&self.loot_table, game_ref(ctx.plugins).difficulty)
+                    {
+                        Item::add_to_scene(
+                            ctx.scene,
+                            ctx.resource_manager.clone(),
+                            entry.kind(),
+                            entry.amount(),
+                            position + Vector3::new(0.0, 0.5, 0.0),
+                            true,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Checks whether the given node is the root node itself or one of its descendants.
+pub fn is_part_of(graph: &Graph, mut node: Handle<Node>, root: Handle<Node>) -> bool {
+    while let Some(node_ref) = graph.try_get(node) {
+        if node == root {
+            return true;
+        }
+        node = node_ref.parent();
+    }
+    false
+}
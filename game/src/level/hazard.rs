@@ -0,0 +1,91 @@
+use crate::{
+    character::{CharacterMessage, CharacterMessageData},
+    current_level_ref,
+    status_effect::StatusEffectKind,
+};
+use fyrox::{
+    core::{
+        algebra::{Point3, Vector3},
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    impl_component_provider,
+    scene::node::TypeUuidProvider,
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// A volume (fire, toxic waste, etc.) that applies a status effect to every actor inside it.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Hazard {
+    effect: StatusEffectKind,
+
+    #[reflect(
+        description = "Time (in seconds) between two consecutive applications of the effect."
+    )]
+    interval: f32,
+
+    #[reflect(
+        description = "Half-size of the volume along each axis, in local coordinates of the hazard."
+    )]
+    #[visit(optional)]
+    extents: Vector3<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    timer: f32,
+}
+
+impl Default for Hazard {
+    fn default() -> Self {
+        Self {
+            effect: StatusEffectKind::Burning,
+            interval: 0.5,
+            extents: Vector3::new(1.0, 1.0, 1.0),
+            timer: 0.0,
+        }
+    }
+}
+
+impl_component_provider!(Hazard);
+
+impl TypeUuidProvider for Hazard {
+    fn type_uuid() -> Uuid {
+        uuid!("f0e9260f-501e-44f6-85a4-051af17d8b7b")
+    }
+}
+
+impl ScriptTrait for Hazard {
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        self.timer -= context.dt;
+        if self.timer > 0.0 {
+            return;
+        }
+        self.timer = self.interval;
+
+        // The hazard could be any node (a pivot, a collider, etc.), so its volume is set explicitly
+        // instead of taking the bounds of the node.
+        let inv_transform = context.scene.graph[context.handle]
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_default();
+        for &actor in current_level_ref(context.plugins).unwrap().actors.iter() {
+            let local_position = inv_transform
+                .transform_point(&Point3::from(context.scene.graph[actor].global_position()))
+                .coords;
+            if local_position.x.abs() <= self.extents.x
+                && local_position.y.abs() <= self.extents.y
+                && local_position.z.abs() <= self.extents.z
+            {
+                context.message_sender.send_global(CharacterMessage {
+                    character: actor,
+                    data: CharacterMessageData::ApplyStatusEffect(self.effect),
+                })
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{block_on, current_level_mut, weapon::definition::WeaponKind};
+use crate::{block_on, current_level_mut, item_effect::ItemEffect, weapon::definition::WeaponKind};
 use fyrox::{
     core::{
         algebra::{Point3, Vector3},
@@ -41,6 +41,7 @@ use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
     EnumString,
     EnumVariantNames,
 )]
+// Kinds are saved by index of a variant, so new variants must be added only at the end.
 pub enum ItemKind {
     Medkit,
     Medpack,
@@ -58,6 +59,31 @@ pub enum ItemKind {
 
     // Keys
     MasterKey,
+
+    // Used to upgrade weapons on work benches.
+    UpgradeComponent,
+
+    // Special grenades.
+    StasisGrenade,
+    IncendiaryGrenade,
+    EmpGrenade,
+
+    // Crafting ingredients.
+    MedicalGel,
+    Bandage,
+    AmmoParts,
+
+    // Restores armor of the suit.
+    ArmorPlate,
+
+    // Equipment of the suit.
+    Battery,
+    RegenerationModule,
+    DampenerModule,
+    StorageModule,
+
+    // Unlocks a journal entry when used.
+    DataPad,
 }
 
 impl Default for ItemKind {
@@ -76,9 +102,22 @@ impl ItemKind {
             ItemKind::RailGun => Some(WeaponKind::RailGun),
             ItemKind::Medkit
             | ItemKind::Medpack
+            | ItemKind::ArmorPlate
             | ItemKind::Ammo
             | ItemKind::Grenade
-            | ItemKind::MasterKey => None,
+            | ItemKind::StasisGrenade
+            | ItemKind::IncendiaryGrenade
+            | ItemKind::EmpGrenade
+            | ItemKind::MasterKey
+            | ItemKind::UpgradeComponent
+            | ItemKind::DataPad
+            | ItemKind::MedicalGel
+            | ItemKind::Bandage
+            | ItemKind::AmmoParts
+            | ItemKind::Battery
+            | ItemKind::RegenerationModule
+            | ItemKind::DampenerModule
+            | ItemKind::StorageModule => None,
         }
     }
 }
@@ -182,6 +221,14 @@ pub struct ItemDefinition {
     pub name: String,
     pub consumable: bool,
     pub preview: String,
+    /// Maximum amount of items in a single inventory stack.
+    pub max_stack: u32,
+    /// Amount of inventory slots a single stack occupies.
+    pub slot_size: u32,
+    /// Weight (in kilograms) of a single item.
+    pub weight: f32,
+    /// Effects that are applied when the item is used.
+    pub effects: Vec<ItemEffect>,
 }
 
 #[derive(Deserialize, Default)]
@@ -208,10 +255,12 @@ impl Item {
             .unwrap_or_else(|| panic!("No definition for {kind:?} weapon!"))
     }
 
+    /// Spawns an item that carries the given amount of items of the same kind.
     pub fn add_to_scene(
         scene: &mut Scene,
         resource_manager: ResourceManager,
         kind: ItemKind,
+        stack_size: u32,
         position: Vector3<f32>,
         adjust_height: bool,
     ) {
@@ -250,9 +299,13 @@ impl Item {
 
         let item_ref = &mut scene.graph[item];
 
-        assert!(item_ref.has_script::<Item>());
-
         item_ref.local_transform_mut().set_position(position);
+
+        // Root node must have Item script.
+        item_ref
+            .try_get_script_mut::<Item>()
+            .expect("Item prefab must have Item script!")
+            .stack_size = stack_size.max(1);
     }
 
     pub fn get_kind(&self) -> ItemKind {
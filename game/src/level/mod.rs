@@ -1,19 +1,27 @@
 use crate::{
-    bot::Bot, config::SoundConfig, door::DoorContainer, level::item::ItemContainer,
-    sound::SoundManager, utils::use_hrtf, MessageSender,
+    bot::Bot,
+    config::SoundConfig,
+    door::DoorContainer,
+    level::{item::ItemContainer, music::MusicManager},
+    sound::SoundManager,
+    utils::use_hrtf,
+    MessageSender,
 };
 use fyrox::scene::navmesh::NavigationalMesh;
 use fyrox::{
     core::{math::PositionProvider, pool::Handle, visitor::prelude::*},
     engine::resource_manager::ResourceManager,
     plugin::PluginContext,
-    scene::{self, node::Node, Scene},
+    scene::{self, graph::Graph, node::Node, Scene},
 };
 use std::path::Path;
 
 pub mod death_zone;
 pub mod decal;
+pub mod destructible;
+pub mod hazard;
 pub mod item;
+pub mod music;
 pub mod spawn;
 pub mod trigger;
 pub mod turret;
@@ -27,11 +35,19 @@ pub struct Level {
     pub items: ItemContainer,
     pub doors_container: DoorContainer,
     pub elevators: Vec<Handle<Node>>,
+    #[visit(optional)]
+    pub destructibles: Vec<Handle<Node>>,
+    #[visit(optional)]
+    pub workbenches: Vec<Handle<Node>>,
+    #[visit(optional)]
+    pub containers: Vec<Handle<Node>>,
     pub navmesh: Handle<Node>,
 
     #[visit(skip)]
     pub sound_manager: SoundManager,
     #[visit(skip)]
+    pub music_manager: MusicManager,
+    #[visit(skip)]
     sender: Option<MessageSender>,
 }
 
@@ -74,10 +90,14 @@ impl Level {
             items: Default::default(),
             scene: scene_handle,
             sender: Some(sender),
-            sound_manager: SoundManager::new(scene, resource_manager),
+            sound_manager: SoundManager::new(scene, resource_manager.clone()),
+            music_manager: MusicManager::new(scene, resource_manager, ""),
             doors_container: Default::default(),
             map_path: Default::default(),
             elevators: Default::default(),
+            destructibles: Default::default(),
+            workbenches: Default::default(),
+            containers: Default::default(),
         }
     }
 
@@ -124,10 +144,14 @@ impl Level {
             items: Default::default(),
             scene: Handle::NONE, // Filled when scene will be moved to engine.
             sender: Some(sender),
-            sound_manager: SoundManager::new(&mut scene, resource_manager),
+            sound_manager: SoundManager::new(&mut scene, resource_manager.clone()),
+            music_manager: MusicManager::new(&mut scene, resource_manager, &map),
             doors_container: Default::default(),
             map_path: map,
             elevators: Default::default(),
+            destructibles: Default::default(),
+            workbenches: Default::default(),
+            containers: Default::default(),
         };
 
         (level, scene)
@@ -145,6 +169,30 @@ impl Level {
         self.set_message_sender(sender);
         self.sound_manager =
             SoundManager::new(&mut ctx.scenes[self.scene], ctx.resource_manager.clone());
+        self.music_manager = MusicManager::new(
+            &mut ctx.scenes[self.scene],
+            ctx.resource_manager.clone(),
+            &self.map_path,
+        );
+    }
+
+    /// Returns amount of living bots that are attacking the player.
+    pub fn threat_count(&self, graph: &Graph) -> usize {
+        self.actors
+            .iter()
+            .filter(|a| {
+                graph
+                    .try_get(**a)
+                    .and_then(|n| n.try_get_script::<Bot>())
+                    .map_or(false, |bot| !bot.is_dead() && bot.has_target(self.player))
+            })
+            .count()
+    }
+
+    pub fn update_music(&mut self, ctx: &mut PluginContext) {
+        let graph = &mut ctx.scenes[self.scene].graph;
+        let threat_count = self.threat_count(graph);
+        self.music_manager.update(graph, threat_count, ctx.dt);
     }
 
     pub fn set_message_sender(&mut self, sender: MessageSender) {
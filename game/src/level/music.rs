@@ -0,0 +1,231 @@
+//! Music of levels. Every level has three layers of music (ambient, tension and combat) that are
+//! played simultaneously and crossfaded depending on how many bots are attacking the player.
+//! Playlists of levels are defined in `data/configs/music.ron`. Layers are played through the
+//! music bus, so the music volume is applied by the bus.
+
+use crate::sound::SoundCategory;
+use fyrox::{
+    core::{futures::executor::block_on, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    lazy_static::lazy_static,
+    rand::{self, seq::SliceRandom},
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        node::Node,
+        sound::{Sound, SoundBuilder, Status},
+        Scene,
+    },
+    utils::log::Log,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File, path::PathBuf};
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Playlist {
+    pub ambient: Vec<PathBuf>,
+    pub tension: Vec<PathBuf>,
+    pub combat: Vec<PathBuf>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MusicDefinition {
+    /// Speed of crossfading between layers, in units of gain per second.
+    pub crossfade_speed: f32,
+    /// Amount of bots that should attack the player to switch to the combat layer. The tension
+    /// layer is played when there is at least one attacking bot.
+    pub combat_threshold: usize,
+    /// Playlist that is used for levels that does not have their own playlist.
+    pub default: Playlist,
+    /// Playlists of levels, keyed by path of a level.
+    pub levels: HashMap<String, Playlist>,
+}
+
+impl Default for MusicDefinition {
+    fn default() -> Self {
+        Self {
+            crossfade_speed: 0.5,
+            combat_threshold: 2,
+            default: Default::default(),
+            levels: Default::default(),
+        }
+    }
+}
+
+impl MusicDefinition {
+    const PATH: &'static str = "data/configs/music.ron";
+
+    pub fn new() -> Self {
+        match File::open(Self::PATH)
+            .map_err(|e| e.to_string())
+            .and_then(|file| ron::de::from_reader(file).map_err(|e| e.to_string()))
+        {
+            Ok(definition) => definition,
+            Err(error) => {
+                Log::err(format!(
+                    "[Music]: Unable to load {}! Reason: {}",
+                    Self::PATH,
+                    error
+                ));
+                Self::default()
+            }
+        }
+    }
+
+    pub fn playlist(&self, map_path: &str) -> &Playlist {
+        self.levels.get(map_path).unwrap_or(&self.default)
+    }
+}
+
+lazy_static! {
+    static ref DEFINITION: MusicDefinition = MusicDefinition::new();
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MusicLayer {
+    Ambient,
+    Tension,
+    Combat,
+}
+
+impl MusicLayer {
+    const ALL: [MusicLayer; 3] = [MusicLayer::Ambient, MusicLayer::Tension, MusicLayer::Combat];
+
+    fn from_threat_count(count: usize) -> Self {
+        if count == 0 {
+            Self::Ambient
+        } else if count < DEFINITION.combat_threshold {
+            Self::Tension
+        } else {
+            Self::Combat
+        }
+    }
+
+    // Layers that are played instead of this one when it has no track, nearest first.
+    fn fallbacks(self) -> [MusicLayer; 3] {
+        match self {
+            MusicLayer::Ambient => [MusicLayer::Ambient, MusicLayer::Tension, MusicLayer::Combat],
+            MusicLayer::Tension => [MusicLayer::Tension, MusicLayer::Combat, MusicLayer::Ambient],
+            MusicLayer::Combat => [MusicLayer::Combat, MusicLayer::Tension, MusicLayer::Ambient],
+        }
+    }
+
+    fn tracks(self, playlist: &Playlist) -> &[PathBuf] {
+        match self {
+            MusicLayer::Ambient => &playlist.ambient,
+            MusicLayer::Tension => &playlist.tension,
+            MusicLayer::Combat => &playlist.combat,
+        }
+    }
+
+    // Sound nodes of layers are stored in the scene, so they must be found by name when a saved
+    // game is loaded.
+    fn node_name(self) -> &'static str {
+        match self {
+            MusicLayer::Ambient => "__MusicAmbient",
+            MusicLayer::Tension => "__MusicTension",
+            MusicLayer::Combat => "__MusicCombat",
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct LayerState {
+    sound: Handle<Node>,
+    // Gain of the layer in [0; 1] range.
+    weight: f32,
+}
+
+#[derive(Default, Debug)]
+pub struct MusicManager {
+    layers: [LayerState; 3],
+    current: Option<MusicLayer>,
+}
+
+impl MusicManager {
+    pub fn new(scene: &mut Scene, resource_manager: ResourceManager, map_path: &str) -> Self {
+        let playlist = DEFINITION.playlist(map_path);
+
+        let mut layers: [LayerState; 3] = Default::default();
+
+        for (layer, state) in MusicLayer::ALL.into_iter().zip(layers.iter_mut()) {
+            // Remove sounds that were saved with the scene, they're created from scratch.
+            if let Some(existing) = scene
+                .graph
+                .find_by_name_from_root(layer.node_name())
+                .map(|(handle, _)| handle)
+            {
+                scene.graph.remove_node(existing);
+            }
+
+            let path = match layer.tracks(playlist).choose(&mut rand::thread_rng()) {
+                Some(path) => path,
+                None => continue,
+            };
+
+            match block_on(resource_manager.request_sound_buffer(path)) {
+                Ok(buffer) => {
+                    state.sound =
+                        SoundBuilder::new(BaseBuilder::new().with_name(layer.node_name()))
+                            .with_buffer(buffer.into())
+                            .with_audio_bus(SoundCategory::Music.bus_name().to_owned())
+                            .with_looping(true)
+                            .with_status(Status::Playing)
+                            .with_spatial_blend_factor(0.0)
+                            .with_gain(0.0)
+                            .build(&mut scene.graph);
+                }
+                Err(_) => {
+                    Log::err(format!(
+                        "[Music]: Unable to load music track {}!",
+                        path.display()
+                    ));
+                }
+            }
+        }
+
+        Self {
+            layers,
+            current: None,
+        }
+    }
+
+    pub fn current_layer(&self) -> Option<MusicLayer> {
+        self.current
+    }
+
+    fn has_track(&self, layer: MusicLayer) -> bool {
+        self.layers[layer as usize].sound.is_some()
+    }
+
+    /// Crossfades layers towards the one that matches the amount of bots that are attacking the
+    /// player. If the layer has no track, the nearest layer with a track is played instead.
+    pub fn update(&mut self, graph: &mut Graph, threat_count: usize, dt: f32) {
+        let desired = MusicLayer::from_threat_count(threat_count);
+        let current = desired
+            .fallbacks()
+            .into_iter()
+            .find(|layer| self.has_track(*layer))
+            .unwrap_or(desired);
+        self.current = Some(current);
+
+        let step = DEFINITION.crossfade_speed * dt;
+
+        for (layer, state) in MusicLayer::ALL.into_iter().zip(self.layers.iter_mut()) {
+            let target = if layer == current { 1.0 } else { 0.0 };
+
+            state.weight = if state.weight < target {
+                (state.weight + step).min(target)
+            } else {
+                (state.weight - step).max(target)
+            };
+
+            if let Some(sound) = graph
+                .try_get_mut(state.sound)
+                .and_then(|n| n.cast_mut::<Sound>())
+            {
+                sound.set_gain(state.weight);
+            }
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use crate::weapon::projectile::Projectile;
+use crate::weapon::{grenade::EmpMessage, projectile::Projectile};
 use crate::{
     character::{character_ref, try_get_character_ref},
-    current_level_ref,
+    current_level_mut, current_level_ref,
+    level::destructible::DestructibleMessage,
     sound::SoundManager,
     Player,
 };
@@ -28,7 +29,9 @@ use fyrox::{
         node::{Node, TypeUuidProvider},
         Scene,
     },
-    script::{ScriptContext, ScriptTrait},
+    script::{
+        ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait,
+    },
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
@@ -107,6 +110,9 @@ pub struct Turret {
     #[visit(optional)]
     shoot_interval: f32,
 
+    #[visit(optional)]
+    health: f32,
+
     #[reflect(hidden)]
     shoot_timer: f32,
 
@@ -116,6 +122,11 @@ pub struct Turret {
     #[reflect(hidden)]
     target_check_timer: f32,
 
+    // Time left until the turret recovers from EMP.
+    #[reflect(hidden)]
+    #[visit(optional)]
+    emp_timer: f32,
+
     #[reflect(hidden)]
     #[visit(skip)]
     target: Handle<Node>,
@@ -152,6 +163,8 @@ impl Default for Turret {
             target_check_timer: 0.0,
             collider: Default::default(),
             shoot_interval: 0.2,
+            health: 250.0,
+            emp_timer: 0.0,
         }
     }
 }
@@ -165,7 +178,72 @@ impl TypeUuidProvider for Turret {
 }
 
 impl ScriptTrait for Turret {
+    fn on_init(&mut self, ctx: &mut ScriptContext) {
+        current_level_mut(ctx.plugins)
+            .unwrap()
+            .destructibles
+            .push(ctx.handle);
+    }
+
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        ctx.message_dispatcher
+            .subscribe_to::<DestructibleMessage>(ctx.handle);
+        ctx.message_dispatcher
+            .subscribe_to::<EmpMessage>(ctx.handle);
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        if let Some(level) = current_level_mut(ctx.plugins) {
+            if let Some(position) = level
+                .destructibles
+                .iter()
+                .position(|h| *h == ctx.node_handle)
+            {
+                level.destructibles.remove(position);
+            }
+        }
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        if let Some(msg) = message.downcast_ref::<DestructibleMessage>() {
+            if msg.destructible == ctx.handle && !self.is_destroyed() {
+                self.health -= msg.amount;
+
+                if self.is_destroyed() && self.projector.is_some() {
+                    ctx.scene.graph[self.projector].set_visibility(false);
+                }
+            }
+        } else if let Some(msg) = message.downcast_ref::<EmpMessage>() {
+            if msg.target == ctx.handle && !self.is_destroyed() {
+                self.emp_timer = self.emp_timer.max(msg.duration);
+                self.target = Handle::NONE;
+
+                if self.projector.is_some() {
+                    ctx.scene.graph[self.projector].set_visibility(false);
+                }
+            }
+        }
+    }
+
     fn on_update(&mut self, ctx: &mut ScriptContext) {
+        // Destroyed turret is just a piece of scrap.
+        if self.is_destroyed() {
+            return;
+        }
+
+        // Disabled turret does nothing until it recovers.
+        if self.emp_timer > 0.0 {
+            self.emp_timer -= ctx.dt;
+            if self.emp_timer <= 0.0 && self.projector.is_some() {
+                ctx.scene.graph[self.projector].set_visibility(true);
+            }
+            return;
+        }
+
         let level_ref = current_level_ref(ctx.plugins).expect("Level must exist!");
 
         self.update_frustum(ctx.scene);
@@ -348,6 +426,10 @@ impl Turret {
         context.draw_frustum(&self.frustum, Color::from_rgba(0, 200, 0, 255));
     }
 
+    pub fn is_destroyed(&self) -> bool {
+        self.health <= 0.0
+    }
+
     fn update_frustum(&mut self, scene: &Scene) {
         let barrel_stand = &scene.graph[self.barrel_stand];
         let up = barrel_stand.up_vector();
@@ -3,27 +3,35 @@
 pub mod bot;
 pub mod character;
 pub mod config;
+pub mod container;
 pub mod control_scheme;
+pub mod crafting;
 pub mod door;
 pub mod effects;
 pub mod elevator;
 pub mod gui;
 pub mod inventory;
+pub mod item_effect;
 pub mod level;
 pub mod light;
 pub mod loading_screen;
+pub mod loot;
 pub mod menu;
 pub mod message;
 pub mod options_menu;
 pub mod player;
+pub mod ragdoll;
 pub mod sound;
+pub mod status_effect;
 pub mod ui_container;
 pub mod utils;
 pub mod weapon;
+pub mod workbench;
 
 use crate::{
     bot::Bot,
-    config::{Config, SoundConfig},
+    config::{Config, Difficulty, SoundConfig},
+    container::Container,
     control_scheme::ControlScheme,
     door::{ui::DoorUiContainer, Door},
     effects::{beam::Beam, rail::Rail},
@@ -33,16 +41,18 @@ use crate::{
         weapon_display::WeaponDisplay, DeathScreen, FinalScreen,
     },
     level::{
-        death_zone::DeathZone, decal::Decal, item::Item, spawn::CharacterSpawnPoint,
-        turret::Turret, Level,
+        death_zone::DeathZone, decal::Decal, destructible::Destructible, hazard::Hazard,
+        item::Item, spawn::CharacterSpawnPoint, turret::Turret, Level,
     },
     light::AnimatedLight,
     loading_screen::LoadingScreen,
     menu::Menu,
     message::Message,
-    player::{camera::CameraController, Player},
+    player::{camera::CameraController, Player, PlayerPersistentData},
+    sound::{apply_bus_volumes, SoundCategory},
     utils::use_hrtf,
     weapon::{projectile::Projectile, sight::LaserSight, Weapon},
+    workbench::{ui::WorkBenchUiContainer, WorkBench},
 };
 use fyrox::engine::GraphicsContext;
 use fyrox::gui::UserInterface;
@@ -109,11 +119,16 @@ pub struct Game {
     journal_display: JournalDisplay,
     door_ui_container: DoorUiContainer,
     call_button_ui_container: CallButtonUiContainer,
+    workbench_ui_container: WorkBenchUiContainer,
+    // State of the player that is carried to the next level, it is taken by the player of the
+    // new level when it starts.
+    player_persistent_data: Option<PlayerPersistentData>,
     // We're storing sound config separately because we can adjust sound
     // setting in the options but don't have a level loaded. This field
     // is data-model for options menu.
     sound_config: SoundConfig,
     show_debug_info: bool,
+    difficulty: Difficulty,
     smaller_font: SharedFont,
 }
 
@@ -191,10 +206,12 @@ impl Game {
         let mut control_scheme = ControlScheme::default();
         let mut sound_config = SoundConfig::default();
         let mut show_debug_info = false;
+        let mut difficulty = Difficulty::default();
 
         match Config::load() {
             Ok(config) => {
                 show_debug_info = config.show_debug_info;
+                difficulty = config.difficulty;
                 sound_config = config.sound;
                 control_scheme = config.controls;
             }
@@ -232,6 +249,7 @@ impl Game {
 
         let mut game = Game {
             show_debug_info,
+            difficulty,
             loading_screen: LoadingScreen::new(&mut context.user_interface.build_ctx()),
             running: true,
             menu: fyrox::core::futures::executor::block_on(Menu::new(
@@ -263,6 +281,8 @@ impl Game {
             sound_config,
             door_ui_container: Default::default(),
             call_button_ui_container: Default::default(),
+            workbench_ui_container: Default::default(),
+            player_persistent_data: None,
         };
 
         game.create_debug_ui(&mut context);
@@ -327,6 +347,7 @@ impl Game {
 
             self.door_ui_container.render(renderer);
             self.call_button_ui_container.render(renderer);
+            self.workbench_ui_container.render(renderer);
         }
     }
 
@@ -388,6 +409,7 @@ impl Game {
         self.final_screen.set_visible(context.user_interface, false);
         self.door_ui_container.clear();
         self.call_button_ui_container.clear();
+        self.workbench_ui_container.clear();
 
         // Set control scheme for player.
         if let Some(level) = &mut self.level {
@@ -403,6 +425,7 @@ impl Game {
         if let Some(ref mut level) = self.level.take() {
             self.door_ui_container.clear();
             self.call_button_ui_container.clear();
+            self.workbench_ui_container.clear();
             level.destroy(context);
             Log::info("Current level destroyed!");
         }
@@ -501,6 +524,9 @@ impl Game {
 
         if let Some(ref mut level) = self.level {
             ctx.scenes[level.scene].enabled = !self.menu.is_visible(ctx.user_interface);
+            if ctx.scenes[level.scene].enabled {
+                level.update_music(ctx);
+            }
         }
 
         self.weapon_display.update(ctx.dt);
@@ -508,15 +534,10 @@ impl Game {
         self.item_display.update(ctx.dt);
         self.door_ui_container.update(ctx.dt);
         self.call_button_ui_container.update(ctx.dt);
+        self.workbench_ui_container.update(ctx.dt);
 
         for scene in ctx.scenes.iter_mut() {
-            scene
-                .graph
-                .sound_context
-                .state()
-                .bus_graph_mut()
-                .primary_bus_mut()
-                .set_gain(self.sound_config.master_volume);
+            apply_bus_volumes(&scene.graph.sound_context, &self.sound_config);
         }
 
         self.handle_messages(ctx);
@@ -555,7 +576,15 @@ impl Game {
                         };
 
                         if let Some(kind) = kind {
-                            self.load_level(kind, context)
+                            let persistent_data = context.scenes[level.scene]
+                                .graph
+                                .try_get(level.player)
+                                .and_then(|p| p.try_get_script::<Player>())
+                                .map(|p| p.persistent_data(&context.scenes[level.scene].graph));
+
+                            self.load_level(kind, context);
+
+                            self.player_persistent_data = persistent_data;
                         }
                     }
                 }
@@ -575,10 +604,6 @@ impl Game {
                 }
                 Message::SetMusicVolume(volume) => {
                     self.sound_config.music_volume = *volume;
-                    // TODO: Apply to sound manager of level when it will handle music!
-                    context.scenes[self.menu.scene.scene].graph[self.menu.scene.music]
-                        .as_sound_mut()
-                        .set_gain(*volume);
                 }
                 Message::SetUseHrtf(state) => {
                     self.sound_config.use_hrtf = *state;
@@ -599,12 +624,16 @@ impl Game {
                 Message::SetMasterVolume(volume) => {
                     self.sound_config.master_volume = *volume;
                 }
+                Message::SetBusVolume { category, volume } => {
+                    self.sound_config.set_volume(*category, *volume);
+                }
                 Message::SaveConfig => {
                     match Config::save(
                         context,
                         self.control_scheme.clone(),
                         self.sound_config.clone(),
                         self.show_debug_info,
+                        self.difficulty,
                     ) {
                         Ok(_) => {
                             Log::info("Settings saved!");
@@ -622,11 +651,17 @@ impl Game {
                 }
                 Message::SyncInventory => {
                     if let Some(ref mut level) = self.level {
-                        let player_ref = context.scenes[level.scene].graph[level.player]
-                            .try_get_script::<Player>()
-                            .unwrap();
-                        self.inventory_interface
-                            .sync_to_model(context.resource_manager.clone(), player_ref);
+                        let graph = &context.scenes[level.scene].graph;
+                        let player_ref = graph[level.player].try_get_script::<Player>().unwrap();
+                        let container = graph
+                            .try_get(player_ref.opened_container())
+                            .and_then(|c| c.try_get_script::<Container>())
+                            .map(|c| c.inventory());
+                        self.inventory_interface.sync_to_model(
+                            context.resource_manager.clone(),
+                            player_ref,
+                            container,
+                        );
                     }
                 }
                 Message::SyncJournal => {
@@ -644,6 +679,7 @@ impl Game {
                         let menu_scene = &mut context.scenes[self.menu.scene.scene];
                         SoundBuilder::new(BaseBuilder::new())
                             .with_buffer(buffer.into())
+                            .with_audio_bus(SoundCategory::Ui.bus_name().to_owned())
                             .with_status(Status::Playing)
                             .with_play_once(true)
                             .with_gain(*gain)
@@ -756,18 +792,22 @@ impl PluginConstructor for GameConstructor {
             .add::<Weapon>("Weapon")
             .add::<Item>("Item")
             .add::<Decal>("Decal")
+            .add::<Destructible>("Destructible")
             .add::<Player>("Player")
             .add::<CameraController>("Camera Controller")
             .add::<Bot>("Bot")
             .add::<CharacterSpawnPoint>("Character Spawn Point")
             .add::<DeathZone>("Death Zone")
+            .add::<Hazard>("Hazard")
             .add::<AnimatedLight>("Animated Light")
             .add::<Elevator>("Elevator")
             .add::<CallButton>("Call Button")
             .add::<Projectile>("Projectile")
             .add::<LaserSight>("LaserSight")
             .add::<Rail>("Rail")
-            .add::<Beam>("Beam");
+            .add::<Beam>("Beam")
+            .add::<WorkBench>("Work Bench")
+            .add::<Container>("Container");
     }
 
     fn create_instance(
@@ -0,0 +1,127 @@
+//! Loot tables define what items could be found on bots, in containers and crates.
+
+use crate::{config::Difficulty, inventory::ItemEntry, level::item::ItemKind};
+use fyrox::{
+    lazy_static::lazy_static,
+    rand::{self, seq::SliceRandom, Rng},
+    utils::log::Log,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File};
+
+#[derive(Deserialize, Debug)]
+pub struct LootEntry {
+    pub item: ItemKind,
+    /// Relative probability of the entry to be chosen on a roll.
+    pub weight: f32,
+    /// Inclusive range of amount of items.
+    pub count: (u32, u32),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GuaranteedLoot {
+    pub item: ItemKind,
+    pub count: (u32, u32),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LootTable {
+    /// Inclusive range of amount of weighted entries that are chosen.
+    pub rolls: (u32, u32),
+    pub entries: Vec<LootEntry>,
+    /// Items that are always added.
+    pub guaranteed: Vec<GuaranteedLoot>,
+}
+
+fn random_count(range: (u32, u32), scale: f32) -> u32 {
+    let (min, max) = (range.0.min(range.1), range.0.max(range.1));
+    let count = rand::thread_rng().gen_range(min..=max);
+    (count as f32 * scale).round() as u32
+}
+
+fn add_entry(items: &mut Vec<ItemEntry>, kind: ItemKind, amount: u32) {
+    if amount == 0 {
+        return;
+    }
+
+    if let Some(entry) = items.iter_mut().find(|e| e.kind == kind) {
+        entry.amount += amount;
+    } else {
+        items.push(ItemEntry { kind, amount });
+    }
+}
+
+impl LootTable {
+    /// Generates a random set of items, amounts of items are multiplied by `scale`. Guaranteed
+    /// items are always present, at least one of each.
+    pub fn roll(&self, scale: f32) -> Vec<ItemEntry> {
+        let mut items = Vec::new();
+
+        for guaranteed in self.guaranteed.iter() {
+            add_entry(
+                &mut items,
+                guaranteed.item,
+                random_count(guaranteed.count, scale).max(1),
+            );
+        }
+
+        for _ in 0..random_count(self.rolls, 1.0) {
+            if let Ok(entry) = self
+                .entries
+                .choose_weighted(&mut rand::thread_rng(), |e| e.weight)
+            {
+                add_entry(&mut items, entry.item, random_count(entry.count, scale));
+            }
+        }
+
+        items
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct LootTableContainer {
+    /// Amounts of items are multiplied by the value of the current difficulty.
+    pub difficulty_scaling: HashMap<Difficulty, f32>,
+    pub tables: HashMap<String, LootTable>,
+}
+
+impl LootTableContainer {
+    const PATH: &'static str = "data/configs/loot.ron";
+
+    pub fn new() -> Self {
+        match File::open(Self::PATH)
+            .map_err(|e| e.to_string())
+            .and_then(|file| ron::de::from_reader(file).map_err(|e| e.to_string()))
+        {
+            Ok(container) => container,
+            Err(error) => {
+                Log::err(format!(
+                    "[Loot]: Unable to load {}! Reason: {}",
+                    Self::PATH,
+                    error
+                ));
+                Self::default()
+            }
+        }
+    }
+
+    /// Generates items using a loot table with the given name. Unknown tables give nothing.
+    pub fn generate(&self, table: &str, difficulty: Difficulty) -> Vec<ItemEntry> {
+        match self.tables.get(table) {
+            Some(loot_table) => loot_table.roll(
+                self.difficulty_scaling
+                    .get(&difficulty)
+                    .cloned()
+                    .unwrap_or(1.0),
+            ),
+            None => {
+                Log::err(format!("[Loot]: There is no loot table named {table}!"));
+                Default::default()
+            }
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref LOOT_TABLES: LootTableContainer = LootTableContainer::new();
+}
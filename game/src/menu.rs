@@ -1,6 +1,10 @@
 use crate::{
-    config::SoundConfig, control_scheme::ControlScheme, message::Message,
-    options_menu::OptionsMenu, MessageSender,
+    config::SoundConfig,
+    control_scheme::ControlScheme,
+    message::Message,
+    options_menu::OptionsMenu,
+    sound::{apply_bus_volumes, create_audio_buses, SoundCategory},
+    MessageSender,
 };
 use fyrox::engine::InitializedGraphicsContext;
 use fyrox::{
@@ -55,6 +59,9 @@ impl MenuScene {
 
         scene.ambient_lighting_color = Color::opaque(20, 20, 20);
 
+        create_audio_buses(&scene.graph.sound_context);
+        apply_bus_volumes(&scene.graph.sound_context, sound_config);
+
         let buffer = context
             .resource_manager
             .request_sound_buffer("data/music/Pura Sombar - Tongues falling from an opened sky.ogg")
@@ -63,9 +70,9 @@ impl MenuScene {
 
         let music = SoundBuilder::new(BaseBuilder::new())
             .with_buffer(buffer.into())
+            .with_audio_bus(SoundCategory::Music.bus_name().to_owned())
             .with_looping(true)
             .with_status(Status::Playing)
-            .with_gain(sound_config.music_volume)
             .build(&mut scene.graph);
 
         Self {
@@ -3,6 +3,7 @@
 //! required entity. This is very effective decoupling mechanism that works perfectly with
 //! strict ownership rules of Rust.
 
+use crate::sound::SoundCategory;
 use std::path::PathBuf;
 
 #[derive(Debug)]
@@ -29,4 +30,8 @@ pub enum Message {
     SetMusicVolume(f32),
     SetUseHrtf(bool),
     SetMasterVolume(f32),
+    SetBusVolume {
+        category: SoundCategory,
+        volume: f32,
+    },
 }
@@ -3,6 +3,7 @@ use crate::{
     control_scheme::{ControlButton, ControlScheme},
     gui::{create_check_box, create_scroll_bar, ScrollBarData},
     message::Message,
+    sound::SoundCategory,
     MessageSender,
 };
 use fyrox::engine::{GraphicsContext, InitializedGraphicsContext};
@@ -38,6 +39,7 @@ pub struct OptionsMenu {
     sender: MessageSender,
     sound_volume: Handle<UiNode>,
     pub music_volume: Handle<UiNode>,
+    bus_volumes: Vec<(SoundCategory, Handle<UiNode>)>,
     video_mode: Handle<UiNode>,
     spot_shadows: Handle<UiNode>,
     soft_spot_shadows: Handle<UiNode>,
@@ -333,6 +335,38 @@ impl OptionsMenu {
             },
         };
 
+        // Volume of music is set by its own slider above.
+        let mut bus_volumes = Vec::new();
+        let mut bus_volume_widgets = Vec::new();
+        for (i, (category, name)) in [
+            (SoundCategory::Sfx, "Effects Volume"),
+            (SoundCategory::Voice, "Voices Volume"),
+            (SoundCategory::Ui, "Interface Volume"),
+            (SoundCategory::Ambience, "Ambience Volume"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let row = i + 2;
+            let scroll_bar = create_scroll_bar(
+                ctx,
+                ScrollBarData {
+                    min: 0.0,
+                    max: 1.0,
+                    value: sound_config.volume(category),
+                    step: 0.025,
+                    row,
+                    column: 1,
+                    margin,
+                    show_value: true,
+                    orientation: Orientation::Horizontal,
+                },
+            );
+            bus_volume_widgets.push(make_text_mark(name, row, ctx));
+            bus_volume_widgets.push(scroll_bar);
+            bus_volumes.push((category, scroll_bar));
+        }
+
         let sound_tab = TabDefinition {
             header: make_tab_header("Sound", ctx),
             content: {
@@ -376,14 +410,15 @@ impl OptionsMenu {
                                     );
                                     music_volume
                                 })
-                                .with_child(make_text_mark("Use HRTF", 2, ctx))
+                                .with_children(bus_volume_widgets)
+                                .with_child(make_text_mark("Use HRTF", 6, ctx))
                                 .with_child({
-                                    use_hrtf = create_check_box(ctx, 2, 1, sound_config.use_hrtf);
+                                    use_hrtf = create_check_box(ctx, 6, 1, sound_config.use_hrtf);
                                     use_hrtf
                                 })
                                 .with_child({
                                     reset_audio_settings = ButtonBuilder::new(
-                                        WidgetBuilder::new().on_row(4).with_margin(margin),
+                                        WidgetBuilder::new().on_row(8).with_margin(margin),
                                     )
                                     .with_text("Reset")
                                     .build(ctx);
@@ -393,6 +428,10 @@ impl OptionsMenu {
                         .add_row(common_row)
                         .add_row(common_row)
                         .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
                         .add_row(Row::stretch())
                         .add_row(common_row)
                         .add_column(Column::strict(250.0))
@@ -504,6 +543,7 @@ impl OptionsMenu {
             window: options_window,
             sound_volume,
             music_volume,
+            bus_volumes,
             video_mode,
             spot_shadows,
             soft_spot_shadows,
@@ -575,6 +615,9 @@ impl OptionsMenu {
         sync_scroll_bar(self.mouse_sens, control_scheme.mouse_sens);
         sync_scroll_bar(self.sound_volume, sound_config.master_volume);
         sync_scroll_bar(self.music_volume, sound_config.music_volume);
+        for &(category, scroll_bar) in self.bus_volumes.iter() {
+            sync_scroll_bar(scroll_bar, sound_config.volume(category));
+        }
 
         for (btn, def) in self
             .control_scheme_buttons
@@ -731,6 +774,16 @@ impl OptionsMenu {
                 } else if message.destination() == self.music_volume {
                     self.sender.send(Message::SetMusicVolume(*new_value));
                     changed = true;
+                } else if let Some(&(category, _)) = self
+                    .bus_volumes
+                    .iter()
+                    .find(|(_, scroll_bar)| message.destination() == *scroll_bar)
+                {
+                    self.sender.send(Message::SetBusVolume {
+                        category,
+                        volume: *new_value,
+                    });
+                    changed = true;
                 }
             }
         } else if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
@@ -0,0 +1,28 @@
+use crate::{
+    character::{ArmorDefinition, FallDamageDefinition, Resistances, StaminaDefinition},
+    player::equipment::EquipmentDefinition,
+};
+use fyrox::lazy_static::lazy_static;
+use serde::Deserialize;
+use std::fs::File;
+
+#[derive(Deserialize, Debug)]
+pub struct PlayerDefinition {
+    pub pain_sounds: Vec<String>,
+    pub fall_damage: FallDamageDefinition,
+    pub stamina: StaminaDefinition,
+    pub resistances: Resistances,
+    pub armor: ArmorDefinition,
+    pub equipment: EquipmentDefinition,
+}
+
+impl PlayerDefinition {
+    pub fn load() -> Self {
+        let file = File::open("data/configs/player.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    pub static ref DEFINITION: PlayerDefinition = PlayerDefinition::load();
+}
@@ -0,0 +1,136 @@
+//! Equipment of the suit: a flash light powered by a battery and passive modules that change
+//! other systems of the player (healing, footsteps, inventory size).
+
+use fyrox::core::{reflect::prelude::*, visitor::prelude::*};
+use serde::Deserialize;
+use strum_macros::AsRefStr;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EquipmentDefinition {
+    pub battery_capacity: f32,
+    /// Amount of charge the flash light spends per second.
+    pub flash_light_drain_rate: f32,
+    pub max_modules: usize,
+    /// Amount of health per second restored by the regeneration module.
+    pub regeneration_rate: f32,
+    /// The regeneration module does not heal above this value.
+    pub regeneration_limit: f32,
+    /// Multiplier of the volume of footsteps when the dampener module is installed.
+    pub dampened_footstep_gain: f32,
+    /// Amount of inventory rows added by the storage module.
+    pub extra_inventory_rows: u32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Visit, Reflect, AsRefStr, Deserialize)]
+pub enum SuitModule {
+    Regeneration,
+    Dampener,
+    Storage,
+}
+
+impl Default for SuitModule {
+    fn default() -> Self {
+        Self::Regeneration
+    }
+}
+
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Equipment {
+    // Charge of the battery in [0; 1] range.
+    battery: f32,
+    flash_light_enabled: bool,
+    modules: Vec<SuitModule>,
+}
+
+impl Default for Equipment {
+    fn default() -> Self {
+        Self {
+            battery: 1.0,
+            flash_light_enabled: false,
+            modules: Default::default(),
+        }
+    }
+}
+
+impl Equipment {
+    /// Returns charge of the battery in [0; 1] range.
+    pub fn battery(&self) -> f32 {
+        self.battery
+    }
+
+    pub fn modules(&self) -> &[SuitModule] {
+        &self.modules
+    }
+
+    pub fn has_module(&self, module: SuitModule) -> bool {
+        self.modules.contains(&module)
+    }
+
+    pub fn is_flash_light_enabled(&self) -> bool {
+        self.flash_light_enabled
+    }
+
+    /// Switches the flash light, it could not be turned on with empty battery.
+    pub fn switch_flash_light(&mut self) {
+        self.flash_light_enabled = !self.flash_light_enabled && self.battery > 0.0;
+    }
+
+    pub fn can_charge_battery(&self) -> bool {
+        self.battery < 1.0
+    }
+
+    /// Adds the given amount of charge (in units of `battery_capacity`) to the battery.
+    pub fn charge_battery(&mut self, amount: f32, definition: &EquipmentDefinition) {
+        self.battery = (self.battery + amount / definition.battery_capacity).min(1.0);
+    }
+
+    pub fn can_install_module(&self, module: SuitModule, definition: &EquipmentDefinition) -> bool {
+        !self.has_module(module) && self.modules.len() < definition.max_modules
+    }
+
+    /// Installs the module, does nothing if the module could not be installed.
+    pub fn install_module(&mut self, module: SuitModule, definition: &EquipmentDefinition) {
+        if self.can_install_module(module, definition) {
+            self.modules.push(module);
+        }
+    }
+
+    /// Drains the battery while the flash light is on, the light turns off once the battery is
+    /// empty.
+    pub fn update(&mut self, definition: &EquipmentDefinition, dt: f32) {
+        if self.flash_light_enabled {
+            self.battery = (self.battery
+                - definition.flash_light_drain_rate / definition.battery_capacity * dt)
+                .max(0.0);
+            if self.battery <= 0.0 {
+                self.flash_light_enabled = false;
+            }
+        }
+    }
+
+    // Hooks that are used by other systems of the player.
+
+    pub fn regeneration_rate(&self, definition: &EquipmentDefinition) -> f32 {
+        if self.has_module(SuitModule::Regeneration) {
+            definition.regeneration_rate
+        } else {
+            0.0
+        }
+    }
+
+    pub fn footstep_gain(&self, definition: &EquipmentDefinition) -> f32 {
+        if self.has_module(SuitModule::Dampener) {
+            definition.dampened_footstep_gain
+        } else {
+            1.0
+        }
+    }
+
+    pub fn extra_inventory_rows(&self, definition: &EquipmentDefinition) -> u32 {
+        if self.has_module(SuitModule::Storage) {
+            definition.extra_inventory_rows
+        } else {
+            0
+        }
+    }
+}
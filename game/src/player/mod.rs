@@ -1,5 +1,9 @@
 use crate::{
-    character::{Character, CharacterMessage, CharacterMessageData},
+    character::{
+        try_get_character_ref, Character, CharacterMessage, CharacterMessageData, DamageDealer,
+        DamagePosition, HitBox,
+    },
+    container::Container,
     control_scheme::ControlButton,
     current_level_mut, current_level_ref,
     door::{door_mut, DoorContainer},
@@ -7,23 +11,34 @@ use crate::{
     game_mut, game_ref,
     gui::journal::Journal,
     inventory::Inventory,
+    item_effect::ItemEffectRegistry,
     level::item::ItemKind,
     message::Message,
-    player::state_machine::{CombatWeaponKind, StateMachine, StateMachineInput},
+    player::{
+        definition::DEFINITION,
+        equipment::Equipment,
+        state_machine::{CombatWeaponKind, StateMachine, StateMachineInput},
+    },
     sound::SoundManager,
     utils,
     weapon::{
-        definition::WeaponKind, projectile::Projectile, try_weapon_mut, try_weapon_ref, weapon_ref,
-        WeaponMessage, WeaponMessageData,
+        definition::{MeleeDefinition, WeaponKind},
+        grenade::{self, GRENADES},
+        projectile::{DamageType, Projectile},
+        try_weapon_mut, try_weapon_ref,
+        upgrade::WeaponUpgrades,
+        weapon_mut, weapon_ref, Weapon, WeaponMessage, WeaponMessageData,
     },
-    CameraController, Elevator, Game, Item,
+    workbench::WorkBench,
+    CameraController, Elevator, Game, Item, MessageSender,
 };
 use fyrox::core::algebra::Vector2;
 use fyrox::core::math::Vector2Ext;
 use fyrox::{
     animation::machine,
     core::{
-        algebra::{UnitQuaternion, Vector3},
+        algebra::{Point3, UnitQuaternion, Vector3},
+        arrayvec::ArrayVec,
         color::Color,
         color_gradient::{ColorGradient, ColorGradientBuilder, GradientPoint},
         futures::executor::block_on,
@@ -37,15 +52,19 @@ use fyrox::{
     engine::resource_manager::ResourceManager,
     event::{DeviceEvent, ElementState, Event, MouseScrollDelta, WindowEvent},
     impl_component_provider,
-    material::{shader::SamplerFallback, PropertyValue},
+    material::{shader::SamplerFallback, PropertyValue, SharedMaterial},
     resource::texture::Texture,
     scene::{
         animation::absm::AnimationBlendingStateMachine,
         base::BaseBuilder,
-        graph::Graph,
-        light::BaseLight,
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        light::{spot::SpotLightBuilder, BaseLight, BaseLightBuilder},
         node::{Node, TypeUuidProvider},
         sprite::SpriteBuilder,
+        transform::TransformBuilder,
         Scene,
     },
     script::{
@@ -54,9 +73,14 @@ use fyrox::{
     },
     utils::log::Log,
 };
-use std::ops::{Deref, DerefMut};
+use std::{
+    f32::consts::FRAC_PI_2,
+    ops::{Deref, DerefMut},
+};
 
 pub mod camera;
+pub mod definition;
+pub mod equipment;
 mod state_machine;
 
 #[derive(Default, Debug)]
@@ -71,6 +95,9 @@ pub struct InputController {
     aim: bool,
     toss_grenade: bool,
     shoot: bool,
+    melee: bool,
+    reload: bool,
+    cycle_fire_mode: bool,
     run: bool,
     action: bool,
     cursor_up: bool,
@@ -115,8 +142,19 @@ impl Default for RequiredWeapon {
 pub struct PlayerPersistentData {
     pub inventory: Inventory,
     pub health: f32,
+    pub armor: f32,
+    pub equipment: Equipment,
     pub current_weapon: u32,
     pub weapons: Vec<WeaponKind>,
+    pub weapon_upgrades: Vec<CarriedUpgrades>,
+}
+
+/// Upgrades of a weapon from the previous level that wait until a weapon of the same kind is
+/// added to the player.
+#[derive(Visit, Default, Clone, Debug)]
+pub struct CarriedUpgrades {
+    pub kind: WeaponKind,
+    pub upgrades: WeaponUpgrades,
 }
 
 #[derive(Visit, Reflect, Debug)]
@@ -142,6 +180,12 @@ pub struct Player {
     inventory_display: Handle<Node>,
     journal_display: Handle<Node>,
     health_cylinder: Handle<Node>,
+    #[visit(optional)]
+    stamina_cylinder: Handle<Node>,
+    #[visit(optional)]
+    armor_cylinder: Handle<Node>,
+    #[visit(optional)]
+    helmet_lamp: Handle<Node>,
     last_health: f32,
     health_color_gradient: ColorGradient,
     v_recoil: SmoothAngle,
@@ -158,6 +202,24 @@ pub struct Player {
     #[reflect(hidden)]
     item_display: Handle<Node>,
 
+    // Points of the preview arc of a grenade throw.
+    #[reflect(hidden)]
+    #[visit(optional)]
+    grenade_trajectory: Vec<Handle<Node>>,
+
+    #[reflect(hidden)]
+    #[visit(optional)]
+    grenade: ItemKind,
+
+    // Time for which the current throw is charged, `None` if the player does not charge a throw.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    grenade_charge: Option<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    grenade_throw_speed: f32,
+
     #[reflect(hidden)]
     #[visit(skip)]
     state_machine: StateMachine,
@@ -172,6 +234,29 @@ pub struct Player {
     #[reflect(hidden)]
     controller: InputController,
 
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hard_landing: bool,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    melee_timer: f32,
+
+    // Upgrades of weapons from the previous level, they're applied to weapons of the same kind
+    // once they're added to the player. Saved, because a weapon could be added after a save.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    carried_upgrades: Vec<CarriedUpgrades>,
+
+    // A container which contents are shown next to the inventory.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    opened_container: Handle<Node>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    equipment: Equipment,
+
     #[visit(skip)]
     #[reflect(hidden)]
     pub script_message_sender: Option<ScriptMessageSender>,
@@ -188,6 +273,9 @@ impl Default for Player {
             model: Default::default(),
             controller: Default::default(),
             health_cylinder: Default::default(),
+            stamina_cylinder: Default::default(),
+            armor_cylinder: Default::default(),
+            helmet_lamp: Default::default(),
             spine: Default::default(),
             hips: Default::default(),
             model_yaw: SmoothAngle {
@@ -219,6 +307,10 @@ impl Default for Player {
             last_health: 100.0,
             health_color_gradient: make_color_gradient(),
             item_display: Default::default(),
+            grenade_trajectory: Default::default(),
+            grenade: ItemKind::Grenade,
+            grenade_charge: None,
+            grenade_throw_speed: 0.0,
             v_recoil: SmoothAngle {
                 angle: 0.0,
                 target: 0.0,
@@ -239,6 +331,11 @@ impl Default for Player {
             state_machine: Default::default(),
             script_message_sender: None,
             target_local_velocity: Default::default(),
+            hard_landing: false,
+            melee_timer: 0.0,
+            carried_upgrades: Default::default(),
+            opened_container: Default::default(),
+            equipment: Default::default(),
         }
     }
 }
@@ -266,7 +363,14 @@ impl Clone for Player {
             inventory_display: self.inventory_display,
             journal_display: self.journal_display,
             item_display: self.item_display,
+            grenade_trajectory: self.grenade_trajectory.clone(),
+            grenade: self.grenade,
+            grenade_charge: self.grenade_charge,
+            grenade_throw_speed: self.grenade_throw_speed,
             health_cylinder: self.health_cylinder,
+            stamina_cylinder: self.stamina_cylinder,
+            armor_cylinder: self.armor_cylinder,
+            helmet_lamp: self.helmet_lamp,
             last_health: self.last_health,
             health_color_gradient: self.health_color_gradient.clone(),
             v_recoil: self.v_recoil.clone(),
@@ -281,10 +385,29 @@ impl Clone for Player {
             state_machine: self.state_machine.clone(),
             script_message_sender: self.script_message_sender.clone(),
             target_local_velocity: self.target_local_velocity,
+            hard_landing: self.hard_landing,
+            melee_timer: self.melee_timer,
+            carried_upgrades: self.carried_upgrades.clone(),
+            opened_container: self.opened_container,
+            equipment: self.equipment.clone(),
         }
     }
 }
 
+// Amount of points in the preview arc of a grenade throw and time between them (in seconds).
+const GRENADE_TRAJECTORY_POINTS: usize = 24;
+const GRENADE_TRAJECTORY_STEP: f32 = 0.06;
+const GRAVITY: f32 = 9.81;
+
+// Cosine of the half-angle of the sector in front of the player which is swept by melee attacks.
+const MELEE_SWEEP_COS: f32 = 0.64;
+
+const EXHAUSTED_COLOR: Color = Color::from_rgba(255, 120, 0, 200);
+const FULL_STAMINA_COLOR: Color = Color::from_rgba(0, 160, 255, 200);
+
+const NO_ARMOR_COLOR: Color = Color::from_rgba(60, 60, 60, 200);
+const FULL_ARMOR_COLOR: Color = Color::from_rgba(200, 200, 255, 200);
+
 fn make_color_gradient() -> ColorGradient {
     ColorGradientBuilder::new()
         .with_point(GradientPoint::new(0.0, Color::from_rgba(255, 0, 0, 200)))
@@ -312,12 +435,34 @@ impl Player {
         PlayerPersistentData {
             inventory: self.inventory.clone(),
             health: self.health,
+            armor: self.armor,
+            equipment: self.equipment.clone(),
             current_weapon: self.current_weapon,
             weapons: self
                 .weapons
                 .iter()
                 .map(|w| weapon_ref(*w, graph).kind())
                 .collect::<Vec<_>>(),
+            weapon_upgrades: self
+                .weapons
+                .iter()
+                .map(|w| weapon_ref(*w, graph))
+                .filter(|w| !w.upgrades().is_empty())
+                .map(|w| CarriedUpgrades {
+                    kind: w.kind(),
+                    upgrades: *w.upgrades(),
+                })
+                .collect::<Vec<_>>(),
+        }
+    }
+
+    /// Moves upgrades that were carried from the previous level to the newly added weapon.
+    fn apply_carried_upgrades(&mut self, kind: WeaponKind, graph: &mut Graph) {
+        if let Some(index) = self.carried_upgrades.iter().position(|u| u.kind == kind) {
+            let carried = self.carried_upgrades.remove(index);
+            if let Some(weapon) = self.weapons.last().and_then(|w| try_weapon_mut(*w, graph)) {
+                weapon.set_upgrades(carried.upgrades);
+            }
         }
     }
 
@@ -343,13 +488,18 @@ impl Player {
 
                 let distance = (item_position - self_position).norm();
                 if distance < 0.75 {
+                    // Partial pickups are allowed, so it is enough if a single item fits in.
+                    let (kind, amount) = self.pickup_items(item.get_kind(), &scene.graph);
+                    let fits = self.inventory.can_add_item(kind, amount);
+
                     game.item_display.sync_to_model(
                         resource_manager.clone(),
                         item.get_kind(),
                         item.stack_size,
+                        fits.err(),
                     );
 
-                    if self.controller.action {
+                    if self.controller.action && fits.is_ok() {
                         script_message_sender.send_to_target(
                             self_handle,
                             CharacterMessage {
@@ -390,6 +540,155 @@ impl Player {
         }
     }
 
+    fn check_workbenches(
+        &mut self,
+        scene: &mut Scene,
+        workbenches: &[Handle<Node>],
+        sound_manager: &SoundManager,
+        sender: &MessageSender,
+    ) {
+        let self_position = self.position(&scene.graph);
+
+        for &workbench_handle in workbenches {
+            let workbench_position = scene.graph[workbench_handle].global_position();
+            if self_position.metric_distance(&workbench_position) >= 1.25 {
+                continue;
+            }
+
+            let workbench = match scene.graph[workbench_handle].try_get_script_mut::<WorkBench>() {
+                Some(workbench) => workbench,
+                None => continue,
+            };
+
+            if self.controller.cursor_up {
+                workbench.select_previous();
+                self.controller.cursor_up = false;
+            } else if self.controller.cursor_down {
+                workbench.select_next();
+                self.controller.cursor_down = false;
+            }
+
+            if self.controller.action {
+                self.controller.action = false;
+
+                let stat = workbench.selected_stat();
+                let current_weapon = self.current_weapon();
+                let upgraded =
+                    if let Some(weapon) = try_weapon_mut(current_weapon, &mut scene.graph) {
+                        let cost = Weapon::definition(weapon.kind()).upgrades.cost;
+                        if weapon.can_upgrade(stat)
+                            && self
+                                .inventory
+                                .try_extract_exact_items(ItemKind::UpgradeComponent, cost)
+                                == cost
+                        {
+                            weapon.upgrade(stat)
+                        } else {
+                            false
+                        }
+                    } else {
+                        false
+                    };
+
+                sound_manager.play_sound(
+                    &mut scene.graph,
+                    if upgraded {
+                        "data/sounds/access_granted.ogg"
+                    } else {
+                        "data/sounds/door_deny.ogg"
+                    },
+                    workbench_position,
+                    1.0,
+                    1.0,
+                    1.0,
+                );
+
+                if upgraded {
+                    sender.send(Message::SyncInventory);
+                }
+            }
+
+            break;
+        }
+    }
+
+    /// Opens a nearby container, locked containers could be opened only with a key. The container
+    /// is closed once the player walks away or hides the inventory.
+    fn check_containers(
+        &mut self,
+        scene: &mut Scene,
+        containers: &[Handle<Node>],
+        sound_manager: &SoundManager,
+        sender: &MessageSender,
+    ) {
+        let self_position = self.position(&scene.graph);
+
+        if self.opened_container.is_some() {
+            let is_far = scene
+                .graph
+                .try_get(self.opened_container)
+                .map_or(true, |c| {
+                    self_position.metric_distance(&c.global_position()) >= 1.25
+                });
+
+            if is_far || !scene.graph[self.inventory_display].visibility() {
+                self.opened_container = Handle::NONE;
+                sender.send(Message::SyncInventory);
+            }
+
+            return;
+        }
+
+        if !self.controller.action {
+            return;
+        }
+
+        for &container_handle in containers {
+            let container_position = scene.graph[container_handle].global_position();
+            if self_position.metric_distance(&container_position) >= 1.25 {
+                continue;
+            }
+
+            let container = match scene.graph[container_handle].try_get_script_mut::<Container>() {
+                Some(container) => container,
+                None => continue,
+            };
+
+            self.controller.action = false;
+
+            let was_locked = container.is_locked();
+            if was_locked && self.inventory.has_key() {
+                container.unlock();
+            }
+            let opened = !container.is_locked();
+
+            sound_manager.play_sound(
+                &mut scene.graph,
+                if !opened {
+                    "data/sounds/door_deny.ogg"
+                } else if was_locked {
+                    "data/sounds/access_granted.ogg"
+                } else {
+                    "data/sounds/door_open.ogg"
+                },
+                container_position,
+                1.0,
+                1.0,
+                1.0,
+            );
+
+            if opened {
+                self.opened_container = container_handle;
+
+                scene.graph[self.journal_display].set_visibility(false);
+                scene.graph[self.inventory_display].set_visibility(true);
+                sender.send(Message::SyncInventory);
+            }
+
+            break;
+        }
+    }
+
     fn check_elevators(&self, scene: &mut Scene, elevators: &[Handle<Node>]) {
         let graph = &mut scene.graph;
         let self_position = graph[self.body].global_position();
@@ -536,33 +835,175 @@ impl Player {
             .take_events();
         while let Some(event) = events.pop_front() {
             if event.name == StateMachine::TOSS_GRENADE_SIGNAL {
-                let position = scene.graph[self.weapon_pivot].global_position();
-
-                let direction = scene
-                    .graph
-                    .try_get(self.camera_controller)
-                    .and_then(|c| c.try_get_script::<CameraController>())
-                    .map(|c| scene.graph[c.camera()].look_vector())
-                    .unwrap_or_default();
-
-                if self.inventory.try_extract_exact_items(ItemKind::Grenade, 1) == 1 {
-                    if let Ok(grenade) = block_on(
-                        resource_manager.request_model("data/models/grenade/grenade_proj.rgs"),
-                    ) {
-                        Projectile::spawn(
+                self.controller.toss_grenade = false;
+
+                let (position, direction) = self.grenade_throw_origin(&scene.graph);
+                let definition = grenade::DEFINITIONS.get(self.grenade);
+
+                if self.inventory.try_extract_exact_items(self.grenade, 1) == 1 {
+                    if let Ok(grenade) =
+                        block_on(resource_manager.request_model(&definition.projectile))
+                    {
+                        let grenade = Projectile::spawn(
                             &grenade,
                             scene,
                             direction,
                             position,
                             self_handle,
-                            direction.scale(15.0),
+                            direction.scale(self.grenade_throw_speed),
                         );
+                        if let Some(projectile) =
+                            scene.graph[grenade].try_get_script_mut::<Projectile>()
+                        {
+                            projectile.set_damage_type(definition.damage_type);
+                            projectile.scale_damage(definition.damage_scale);
+                            projectile.set_splash_effects(
+                                definition.status_effect,
+                                definition.emp_duration,
+                            );
+                        }
                     }
                 }
             }
         }
     }
 
+    /// Returns a position and a direction from which grenades are thrown.
+    fn grenade_throw_origin(&self, graph: &Graph) -> (Vector3<f32>, Vector3<f32>) {
+        let position = graph[self.weapon_pivot].global_position();
+
+        let direction = graph
+            .try_get(self.camera_controller)
+            .and_then(|c| c.try_get_script::<CameraController>())
+            .map(|c| graph[c.camera()].look_vector())
+            .unwrap_or_default();
+
+        (position, direction)
+    }
+
+    pub fn equipment(&self) -> &Equipment {
+        &self.equipment
+    }
+
+    /// Returns `false` if the item has no use right now, for example when the battery is full.
+    pub fn can_use_item(&self, item: ItemKind) -> bool {
+        ItemEffectRegistry::can_apply(item, Some((&self.equipment, &DEFINITION.equipment)))
+    }
+
+    pub fn use_item(&mut self, item: ItemKind) {
+        self.character.use_item(
+            item,
+            &DEFINITION.stamina,
+            Some(&mut self.journal),
+            Some((&mut self.equipment, &DEFINITION.equipment)),
+        );
+    }
+
+    /// Drains the battery of the flash light and applies effects of installed modules.
+    fn update_equipment(&mut self, graph: &mut Graph, dt: f32) {
+        let definition = &DEFINITION.equipment;
+
+        self.equipment.update(definition, dt);
+
+        // The flash light belongs to the suit, weapons and the helmet lamp only show it.
+        let flash_light_enabled = self.equipment.is_flash_light_enabled();
+        let current_weapon = self.current_weapon();
+        for &weapon in self.weapons.iter() {
+            if let Some(weapon_ref) = try_weapon_mut(weapon, graph) {
+                weapon_ref.set_flash_light_enabled(flash_light_enabled && weapon == current_weapon);
+            }
+        }
+        if let Some(helmet_lamp) = graph.try_get_mut(self.helmet_lamp) {
+            helmet_lamp.set_visibility(flash_light_enabled);
+        }
+
+        let regeneration_rate = self.equipment.regeneration_rate(definition);
+        if regeneration_rate > 0.0 && self.health < definition.regeneration_limit {
+            let amount = (regeneration_rate * dt).min(definition.regeneration_limit - self.health);
+            self.heal(amount);
+        }
+
+        self.character.footstep_gain = self.equipment.footstep_gain(definition);
+        self.character
+            .inventory
+            .set_extra_rows(self.equipment.extra_inventory_rows(definition));
+    }
+
+    pub fn opened_container(&self) -> Handle<Node> {
+        self.opened_container
+    }
+
+    pub fn selected_grenade(&self) -> ItemKind {
+        self.grenade
+    }
+
+    /// Selects the next kind of grenades that the player has.
+    fn cycle_grenade(&mut self) {
+        let current = GRENADES
+            .iter()
+            .position(|k| *k == self.grenade)
+            .unwrap_or_default();
+        for i in 1..=GRENADES.len() {
+            let kind = GRENADES[(current + i) % GRENADES.len()];
+            if self.inventory.item_count(kind) > 0 {
+                self.grenade = kind;
+                break;
+            }
+        }
+    }
+
+    /// Creates points of the preview arc of a grenade throw. The points are saved with the scene,
+    /// so they're created only if there are no valid points yet.
+    fn create_grenade_trajectory(&mut self, graph: &mut Graph, resource_manager: &ResourceManager) {
+        if self.grenade_trajectory.len() == GRENADE_TRAJECTORY_POINTS
+            && self
+                .grenade_trajectory
+                .iter()
+                .all(|p| graph.is_valid_handle(*p))
+        {
+            return;
+        }
+
+        for point in self.grenade_trajectory.drain(..) {
+            if graph.is_valid_handle(point) {
+                graph.remove_node(point);
+            }
+        }
+
+        let texture = resource_manager.request_texture("data/particles/star_09.png");
+        self.grenade_trajectory = (0..GRENADE_TRAJECTORY_POINTS)
+            .map(|_| {
+                SpriteBuilder::new(BaseBuilder::new().with_visibility(false))
+                    .with_size(0.03)
+                    .with_color(Color::from_rgba(255, 200, 0, 160))
+                    .with_texture(texture.clone())
+                    .build(graph)
+            })
+            .collect();
+    }
+
+    /// Charges the throw while the player holds the button and shows the preview arc of it. The
+    /// arc is hidden when there is no throw being charged.
+    fn update_grenade_trajectory(&mut self, graph: &mut Graph, dt: f32) {
+        let charge = self.grenade_charge.as_mut().map(|charge| {
+            *charge += dt;
+            *charge
+        });
+
+        let (position, direction) = self.grenade_throw_origin(graph);
+        let velocity =
+            direction.scale(grenade::DEFINITIONS.throw_speed(charge.unwrap_or_default()));
+
+        for (i, &point) in self.grenade_trajectory.iter().enumerate() {
+            let t = i as f32 * GRENADE_TRAJECTORY_STEP;
+            let node = &mut graph[point];
+            node.set_visibility(charge.is_some());
+            node.local_transform_mut().set_position(
+                position + velocity.scale(t) - Vector3::new(0.0, GRAVITY * t * t * 0.5, 0.0),
+            );
+        }
+    }
+
     fn update_velocity(&mut self, scene: &mut Scene, dt: f32) {
         let transform = &scene.graph[self.model].global_transform();
 
@@ -584,10 +1025,17 @@ impl Player {
 
         body.set_ang_vel(Default::default());
 
+        // Status effects (such as stun) slow the player down.
+        let movement_factor = self.character.status_effects.movement_factor();
+        let (x, z) = (
+            self.velocity.x * movement_factor,
+            self.velocity.z * movement_factor,
+        );
+
         let velocity = if let Some(new_y_vel) = new_y_vel {
-            Vector3::new(self.velocity.x, new_y_vel, self.velocity.z)
+            Vector3::new(x, new_y_vel, z)
         } else {
-            Vector3::new(self.velocity.x, body.lin_vel().y, self.velocity.z)
+            Vector3::new(x, body.lin_vel().y, z)
         };
 
         body.set_lin_vel(velocity);
@@ -608,7 +1056,7 @@ impl Player {
     }
 
     fn should_be_stunned(&self) -> bool {
-        self.last_health - self.health >= 15.0
+        self.hard_landing || self.last_health - self.health >= 15.0
     }
 
     fn stun(&mut self, scene: &mut Scene) {
@@ -624,6 +1072,7 @@ impl Player {
         }
 
         self.last_health = self.health;
+        self.hard_landing = false;
     }
 
     fn is_walking(&self) -> bool {
@@ -633,9 +1082,8 @@ impl Player {
             || self.controller.walk_left
     }
 
-    fn update_health_cylinder(&self, scene: &mut Scene) {
-        let mesh = scene.graph[self.health_cylinder].as_mesh_mut();
-        let color = self.health_color_gradient.get_color(self.health / 100.0);
+    fn set_indicator_color(graph: &mut Graph, indicator: Handle<Node>, color: Color) {
+        let mesh = graph[indicator].as_mesh_mut();
         let surface = mesh.surfaces_mut().first_mut().unwrap();
         let mut material = surface.material().lock();
         Log::verify(material.set_property(
@@ -646,7 +1094,106 @@ impl Player {
             &ImmutableString::new("emissionStrength"),
             PropertyValue::Vector3(color.as_frgb().scale(10.0)),
         ));
-        drop(material);
+    }
+
+    /// Creates an indicator next to the health cylinder. The indicator is a copy of the health
+    /// cylinder with its own material, so it could have its own color. `side` defines on which
+    /// side of the health cylinder the indicator is placed.
+    fn create_indicator(
+        graph: &mut Graph,
+        health_cylinder: Handle<Node>,
+        name: &str,
+        side: f32,
+    ) -> Handle<Node> {
+        let (indicator, _) = graph.copy_node_inplace(health_cylinder, &mut |_, _| true);
+        let parent = graph[health_cylinder].parent();
+        graph.link_nodes(indicator, parent);
+
+        let health_cylinder_ref = &graph[health_cylinder];
+        let width = health_cylinder_ref
+            .as_mesh()
+            .bounding_box()
+            .half_extents()
+            .x
+            * 2.0
+            * health_cylinder_ref.local_transform().scale().x;
+        let position = **health_cylinder_ref.local_transform().position()
+            + Vector3::new(side * width * 1.5, 0.0, 0.0);
+
+        let indicator_ref = &mut graph[indicator];
+        indicator_ref.set_name(name);
+        indicator_ref.local_transform_mut().set_position(position);
+        if let Some(surface) = indicator_ref.as_mesh_mut().surfaces_mut().first_mut() {
+            let material = surface.material().lock().clone();
+            surface.set_material(SharedMaterial::new(material));
+        }
+
+        indicator
+    }
+
+    /// Creates a spot light attached to the camera, so it always lights the point the player is
+    /// looking at.
+    fn create_helmet_lamp(&self, graph: &mut Graph) -> Handle<Node> {
+        let camera = match graph
+            .try_get(self.camera_controller)
+            .and_then(|c| c.try_get_script::<CameraController>())
+        {
+            Some(camera_controller) => camera_controller.camera(),
+            None => return Handle::NONE,
+        };
+
+        let lamp = SpotLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new()
+                .with_name("HelmetLamp")
+                .with_visibility(false)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        // Spot lights shine along -Y axis, turn it to the look direction of the
+                        // camera.
+                        .with_local_rotation(UnitQuaternion::from_axis_angle(
+                            &Vector3::x_axis(),
+                            -FRAC_PI_2,
+                        ))
+                        .build(),
+                ),
+        ))
+        .with_distance(12.0)
+        .with_hotspot_cone_angle(30.0f32.to_radians())
+        .with_falloff_angle_delta(10.0f32.to_radians())
+        .build(graph);
+
+        graph.link_nodes(lamp, camera);
+
+        lamp
+    }
+
+    fn update_stamina_cylinder(&self, scene: &mut Scene) {
+        if scene.graph.is_valid_handle(self.stamina_cylinder) {
+            let k = self.stamina / DEFINITION.stamina.max;
+            let color = if self.can_run() {
+                EXHAUSTED_COLOR.lerp(FULL_STAMINA_COLOR, k)
+            } else {
+                EXHAUSTED_COLOR
+            };
+            Self::set_indicator_color(&mut scene.graph, self.stamina_cylinder, color);
+        }
+    }
+
+    fn update_armor_cylinder(&self, scene: &mut Scene) {
+        if scene.graph.is_valid_handle(self.armor_cylinder) {
+            let k = if DEFINITION.armor.max > 0.0 {
+                self.armor / DEFINITION.armor.max
+            } else {
+                0.0
+            };
+            let color = NO_ARMOR_COLOR.lerp(FULL_ARMOR_COLOR, k);
+            Self::set_indicator_color(&mut scene.graph, self.armor_cylinder, color);
+        }
+    }
+
+    fn update_health_cylinder(&self, scene: &mut Scene) {
+        let color = self.health_color_gradient.get_color(self.health / 100.0);
+        Self::set_indicator_color(&mut scene.graph, self.health_cylinder, color);
         scene.graph[self.rig_light]
             .query_component_mut::<BaseLight>()
             .unwrap()
@@ -679,6 +1226,10 @@ impl Player {
             machine: self.machine,
             weapon_kind,
             toss_grenade: self.controller.toss_grenade,
+            melee: self
+                .state_machine
+                .is_melee_in_progress(scene, self.animation_player),
+            reload: self.is_reloading(&scene.graph),
             change_weapon: self.weapon_change_direction != RequiredWeapon::None,
             scene,
             local_velocity: self.local_velocity,
@@ -745,6 +1296,24 @@ impl Player {
         self.v_recoil.update(dt);
         self.h_recoil.update(dt);
 
+        self.character.update_reload(&mut scene.graph, dt);
+
+        let mut events =
+            utils::fetch_animation_container_mut(&mut scene.graph, self.animation_player)
+                .get_mut(self.state_machine.reload_animation)
+                .take_events();
+        while let Some(event) = events.pop_front() {
+            if event.name == StateMachine::RELOAD_SIGNAL {
+                self.character.finish_reload(&mut scene.graph);
+            }
+        }
+
+        if self.controller.reload {
+            // Each press makes a single attempt to reload.
+            self.controller.reload = false;
+            self.begin_reload(scene);
+        }
+
         if let Some(&current_weapon_handle) = self
             .character
             .weapons
@@ -756,6 +1325,16 @@ impl Player {
                 .map(|l| l.active_state() == self.state_machine.aim_state)
                 .unwrap_or(false);
 
+            let is_walking = self.is_walking();
+            let is_running = self.is_running(scene);
+            let current_weapon = weapon_mut(current_weapon_handle, &mut scene.graph);
+            current_weapon.update_spread(is_walking, is_running, self.controller.aim, dt);
+
+            if self.controller.cycle_fire_mode {
+                self.controller.cycle_fire_mode = false;
+                current_weapon.cycle_fire_mode();
+            }
+
             if aiming {
                 let ammo_indicator_offset =
                     *weapon_ref(current_weapon_handle, &scene.graph).ammo_indicator_offset;
@@ -765,16 +1344,14 @@ impl Player {
                     .local_transform_mut()
                     .set_position(ammo_indicator_offset);
 
-                let current_weapon = weapon_ref(current_weapon_handle, &scene.graph);
-                if self.controller.shoot && current_weapon.can_shoot(elapsed_time) {
-                    let ammo_per_shot =
-                        *weapon_ref(current_weapon_handle, &scene.graph).ammo_consumption_per_shot;
+                if weapon_mut(current_weapon_handle, &mut scene.graph).update_trigger(
+                    self.controller.shoot,
+                    elapsed_time,
+                    dt,
+                ) {
+                    if weapon_mut(current_weapon_handle, &mut scene.graph).try_consume_ammo() {
+                        let current_weapon = weapon_ref(current_weapon_handle, &scene.graph);
 
-                    if self
-                        .inventory
-                        .try_extract_exact_items(ItemKind::Ammo, ammo_per_shot)
-                        == ammo_per_shot
-                    {
                         script_message_sender.send_to_target(
                             current_weapon_handle,
                             WeaponMessage {
@@ -785,10 +1362,11 @@ impl Player {
                             },
                         );
 
+                        let recoil_factor = self.character.status_effects.recoil_factor();
                         self.v_recoil
-                            .set_target(current_weapon.gen_v_recoil_angle());
+                            .set_target(current_weapon.gen_v_recoil_angle() * recoil_factor);
                         self.h_recoil
-                            .set_target(current_weapon.gen_h_recoil_angle());
+                            .set_target(current_weapon.gen_h_recoil_angle() * recoil_factor);
 
                         if let Some(camera_controller) = scene
                             .graph
@@ -797,10 +1375,213 @@ impl Player {
                         {
                             camera_controller.request_shake_camera();
                         }
+                    } else {
+                        // Empty magazine is reloaded automatically.
+                        self.begin_reload(scene);
                     }
                 }
             } else {
                 scene.graph[self.weapon_display].set_visibility(false);
+
+                // Releases the trigger, so the weapon won't shoot right after aiming.
+                weapon_mut(current_weapon_handle, &mut scene.graph).update_trigger(
+                    false,
+                    elapsed_time,
+                    dt,
+                );
+            }
+        }
+    }
+
+    fn begin_reload(&mut self, scene: &mut Scene) {
+        if self.character.try_begin_reload(&mut scene.graph) {
+            let reload_time = try_weapon_ref(self.current_weapon(), &scene.graph)
+                .map_or(1.0, |w| w.reload_time());
+            let animation =
+                utils::fetch_animation_container_mut(&mut scene.graph, self.animation_player)
+                    .get_mut(self.state_machine.reload_animation);
+            // Stretch the animation to the reload time, the magazine is filled by its signal.
+            let speed = animation.length() / reload_time.max(f32::EPSILON);
+            animation.set_speed(speed).set_enabled(true).rewind();
+        }
+    }
+
+    fn melee_definition(&self, graph: &Graph) -> &'static MeleeDefinition {
+        try_weapon_ref(self.current_weapon(), graph)
+            .map_or(&MeleeDefinition::UNARMED, |w| &w.definition.melee)
+    }
+
+    fn update_melee(
+        &mut self,
+        scene: &mut Scene,
+        self_handle: Handle<Node>,
+        actors: &[Handle<Node>],
+        script_message_sender: &ScriptMessageSender,
+        dt: f32,
+    ) {
+        self.melee_timer -= dt;
+
+        let definition = self.melee_definition(&scene.graph);
+
+        if self.controller.melee {
+            // Each press makes a single attack.
+            self.controller.melee = false;
+
+            // A swing must not be restarted until the previous one is finished.
+            let is_swinging = self
+                .state_machine
+                .upper_body_layer(&scene.graph)
+                .map_or(false, |l| {
+                    l.active_state() == self.state_machine.melee_state
+                });
+
+            if self.melee_timer <= 0.0
+                && !is_swinging
+                && !self.state_machine.is_stunned(scene, self.animation_player)
+                && self
+                    .character
+                    .try_spend_stamina(DEFINITION.stamina.melee_cost)
+            {
+                self.melee_timer = definition.cooldown;
+
+                utils::fetch_animation_container_mut(&mut scene.graph, self.animation_player)
+                    .get_mut(self.state_machine.melee_animation)
+                    .set_enabled(true)
+                    .rewind();
+            }
+        }
+
+        // Damage is applied when the swing reaches the target, not when the button is pressed.
+        let mut events =
+            utils::fetch_animation_container_mut(&mut scene.graph, self.animation_player)
+                .get_mut(self.state_machine.melee_animation)
+                .take_events();
+        while let Some(event) = events.pop_front() {
+            if event.name == StateMachine::MELEE_HIT_SIGNAL {
+                self.melee_hit(
+                    &scene.graph,
+                    self_handle,
+                    actors,
+                    definition,
+                    script_message_sender,
+                );
+            }
+        }
+    }
+
+    /// Checks that nothing (a wall, a closed door, etc.) is between the player and the point on
+    /// the target.
+    fn can_reach(
+        &self,
+        graph: &Graph,
+        origin: Vector3<f32>,
+        target: &Character,
+        point: Vector3<f32>,
+    ) -> bool {
+        let mut query_storage = ArrayVec::<Intersection, 64>::new();
+
+        let ray_direction = point - origin;
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(origin),
+                ray_direction,
+                max_len: ray_direction.norm(),
+                groups: Default::default(),
+                sort_results: true,
+            },
+            &mut query_storage,
+        );
+
+        let belongs_to = |character: &Character, collider: Handle<Node>| {
+            character.capsule_collider == collider
+                || character.hit_boxes.iter().any(|h| h.collider == collider)
+        };
+
+        query_storage
+            .iter()
+            .find(|i| !belongs_to(&self.character, i.collider))
+            .map_or(true, |i| belongs_to(target, i.collider))
+    }
+
+    /// Sweeps a sector in front of the player and damages every actor in it that is not behind an
+    /// obstacle. Damage is applied to the closest hit box of an actor, so the target will react to
+    /// the hit.
+    fn melee_hit(
+        &self,
+        graph: &Graph,
+        self_handle: Handle<Node>,
+        actors: &[Handle<Node>],
+        definition: &MeleeDefinition,
+        script_message_sender: &ScriptMessageSender,
+    ) {
+        let origin = self.position(graph);
+        let look = graph[self.model].look_vector();
+        let forward = Vector3::new(look.x, 0.0, look.z)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::z);
+
+        for &actor_handle in actors.iter().filter(|a| **a != self_handle) {
+            let character = match try_get_character_ref(actor_handle, graph) {
+                Some(character) if !character.is_dead() => character,
+                _ => continue,
+            };
+
+            let candidates = if character.hit_boxes.is_empty() {
+                vec![(None, character.position(graph))]
+            } else {
+                character
+                    .hit_boxes
+                    .iter()
+                    .map(|h| (Some(*h), graph[h.collider].global_position()))
+                    .collect::<Vec<_>>()
+            };
+
+            let mut closest: Option<(f32, Option<HitBox>, Vector3<f32>)> = None;
+            for (hit_box, point) in candidates {
+                let offset = point - origin;
+                let distance = offset.norm();
+                if distance > definition.range {
+                    continue;
+                }
+
+                if let Some(direction) =
+                    Vector3::new(offset.x, 0.0, offset.z).try_normalize(f32::EPSILON)
+                {
+                    if direction.dot(&forward) < MELEE_SWEEP_COS {
+                        continue;
+                    }
+                }
+
+                if !self.can_reach(graph, origin, character, point) {
+                    continue;
+                }
+
+                if closest.map_or(true, |(closest_distance, ..)| distance < closest_distance) {
+                    closest = Some((distance, hit_box, point));
+                }
+            }
+
+            if let Some((_, hitbox, point)) = closest {
+                script_message_sender.send_to_target(
+                    actor_handle,
+                    CharacterMessage {
+                        character: actor_handle,
+                        data: CharacterMessageData::Damage {
+                            dealer: DamageDealer {
+                                entity: self_handle,
+                            },
+                            hitbox,
+                            amount: definition.damage * hitbox.map_or(1.0, |h| h.damage_factor),
+                            kind: DamageType::Melee,
+                            critical_hit_probability: 0.0,
+                            // Direction of the hit is used to knock back body parts of the target.
+                            position: Some(DamagePosition {
+                                point,
+                                direction: forward,
+                            }),
+                        },
+                    },
+                );
             }
         }
     }
@@ -853,6 +1634,7 @@ impl Player {
     fn is_running(&self, scene: &Scene) -> bool {
         !self.is_dead()
             && self.controller.run
+            && self.can_run()
             && !self.controller.aim
             && !self.state_machine.is_stunned(scene, self.animation_player)
     }
@@ -950,14 +1732,18 @@ impl ScriptTrait for Player {
             .with_size(0.1)
             .build(&mut context.scene.graph);
 
-        // Add default weapons.
-        for weapon in [
-            WeaponKind::Glock,
-            WeaponKind::M4,
-            WeaponKind::Ak47,
-            WeaponKind::PlasmaRifle,
-            WeaponKind::RailGun,
-        ] {
+        // Weapons from the previous level replace the default ones.
+        let weapons = match game_ref(context.plugins).player_persistent_data.as_ref() {
+            Some(persistent_data) => persistent_data.weapons.clone(),
+            None => vec![
+                WeaponKind::Glock,
+                WeaponKind::M4,
+                WeaponKind::Ak47,
+                WeaponKind::PlasmaRifle,
+                WeaponKind::RailGun,
+            ],
+        };
+        for weapon in weapons {
             context.message_sender.send_to_target(
                 context.handle,
                 CharacterMessage {
@@ -967,7 +1753,9 @@ impl ScriptTrait for Player {
             );
         }
 
-        self.inventory.add_item(ItemKind::Grenade, 10);
+        Log::verify(self.inventory.add_item(ItemKind::Grenade, 10));
+
+        self.stamina = DEFINITION.stamina.max;
 
         let level = current_level_mut(context.plugins).unwrap();
 
@@ -977,8 +1765,50 @@ impl ScriptTrait for Player {
     }
 
     fn on_start(&mut self, ctx: &mut ScriptContext) {
+        // Weapons of the persistent data are added in `on_init`. The current weapon is not
+        // restored, the last added weapon is selected, as on the start of every level.
+        if let Some(persistent_data) = game_mut(ctx.plugins).player_persistent_data.take() {
+            self.inventory = persistent_data.inventory;
+            // Items of weapons are added back to the inventory when the weapons are added.
+            for weapon in persistent_data.weapons.iter() {
+                self.inventory
+                    .try_extract_exact_items(weapon.associated_item(), 1);
+            }
+            self.health = persistent_data.health;
+            self.last_health = persistent_data.health;
+            self.carried_upgrades = persistent_data.weapon_upgrades;
+            self.armor = persistent_data.armor;
+            self.equipment = persistent_data.equipment;
+        }
+
         let game = game_ref(ctx.plugins);
 
+        self.character.resistances = &DEFINITION.resistances;
+        self.character.armor_definition = &DEFINITION.armor;
+
+        // Indicators are not a part of the prefab, they're created once and then saved with the
+        // scene.
+        if !ctx.scene.graph.is_valid_handle(self.stamina_cylinder) {
+            self.stamina_cylinder = Self::create_indicator(
+                &mut ctx.scene.graph,
+                self.health_cylinder,
+                "StaminaIndicator",
+                1.0,
+            );
+        }
+        self.create_grenade_trajectory(&mut ctx.scene.graph, ctx.resource_manager);
+        if !ctx.scene.graph.is_valid_handle(self.helmet_lamp) {
+            self.helmet_lamp = self.create_helmet_lamp(&mut ctx.scene.graph);
+        }
+        if !ctx.scene.graph.is_valid_handle(self.armor_cylinder) {
+            self.armor_cylinder = Self::create_indicator(
+                &mut ctx.scene.graph,
+                self.health_cylinder,
+                "ArmorIndicator",
+                -1.0,
+            );
+        }
+
         ctx.message_dispatcher
             .subscribe_to::<CharacterMessage>(ctx.handle);
         ctx.message_dispatcher
@@ -986,7 +1816,7 @@ impl ScriptTrait for Player {
 
         self.script_message_sender = Some(ctx.message_sender.clone());
 
-        self.state_machine = StateMachine::new(self.machine, &ctx.scene.graph).unwrap();
+        self.state_machine = StateMachine::new(self.machine, &mut ctx.scene.graph).unwrap();
 
         self.resolve(
             ctx.scene,
@@ -1091,6 +1921,12 @@ impl ScriptTrait for Player {
             } else if button == control_scheme.move_right.button {
                 self.controller.walk_right = state == ElementState::Pressed;
             } else if button == control_scheme.jump.button {
+                let can_jump = can_jump
+                    && (state != ElementState::Pressed
+                        || self
+                            .character
+                            .try_spend_stamina(DEFINITION.stamina.jump_cost));
+
                 if state == ElementState::Pressed && can_jump {
                     let animations_container = utils::fetch_animation_container_mut(
                         &mut context.scene.graph,
@@ -1113,11 +1949,7 @@ impl ScriptTrait for Player {
                 self.controller.run = state == ElementState::Pressed;
             } else if button == control_scheme.flash_light.button {
                 if state == ElementState::Pressed {
-                    if let Some(current_weapon) =
-                        try_weapon_mut(self.current_weapon(), &mut context.scene.graph)
-                    {
-                        current_weapon.switch_flash_light();
-                    }
+                    self.equipment.switch_flash_light();
                 }
             } else if button == control_scheme.grab_ak47.button && can_change_weapon {
                 if current_weapon_kind.map_or(false, |k| k != WeaponKind::Ak47) {
@@ -1148,22 +1980,40 @@ impl ScriptTrait for Player {
                     weapon_change_direction = Some(RequiredWeapon::Previous);
                 }
             } else if button == control_scheme.toss_grenade.button {
-                if self.inventory.item_count(ItemKind::Grenade) > 0 {
-                    self.controller.toss_grenade = state == ElementState::Pressed;
-                    if state == ElementState::Pressed {
-                        let animations_container = utils::fetch_animation_container_mut(
-                            &mut context.scene.graph,
-                            self.animation_player,
-                        );
-
-                        animations_container
-                            .get_mut(self.state_machine.toss_grenade_animation)
-                            .set_enabled(true)
-                            .rewind();
+                if state == ElementState::Pressed {
+                    // Throw is charged while the button is held and made when it is released.
+                    if self.grenade_charge.is_none()
+                        && !self.controller.toss_grenade
+                        && self.inventory.item_count(self.grenade) > 0
+                    {
+                        self.grenade_charge = Some(0.0);
                     }
+                } else if let Some(charge) = self.grenade_charge.take() {
+                    self.grenade_throw_speed = grenade::DEFINITIONS.throw_speed(charge);
+                    self.controller.toss_grenade = true;
+
+                    let animations_container = utils::fetch_animation_container_mut(
+                        &mut context.scene.graph,
+                        self.animation_player,
+                    );
+
+                    animations_container
+                        .get_mut(self.state_machine.toss_grenade_animation)
+                        .set_enabled(true)
+                        .rewind();
+                }
+            } else if button == control_scheme.cycle_grenade.button {
+                if state == ElementState::Pressed && self.grenade_charge.is_none() {
+                    self.cycle_grenade();
                 }
             } else if button == control_scheme.shoot.button {
                 self.controller.shoot = state == ElementState::Pressed;
+            } else if button == control_scheme.melee.button {
+                self.controller.melee = state == ElementState::Pressed;
+            } else if button == control_scheme.reload.button {
+                self.controller.reload = state == ElementState::Pressed;
+            } else if button == control_scheme.cycle_fire_mode.button {
+                self.controller.cycle_fire_mode = state == ElementState::Pressed;
             } else if button == control_scheme.cursor_up.button {
                 self.controller.cursor_up = state == ElementState::Pressed;
             } else if button == control_scheme.cursor_down.button {
@@ -1236,6 +2086,10 @@ impl ScriptTrait for Player {
                 ctx.message_sender,
                 &level.sound_manager,
             );
+
+            if let CharacterMessageData::AddWeapon(kind) = char_message.data {
+                self.apply_carried_upgrades(kind, &mut ctx.scene.graph);
+            }
         } else if let Some(weapon_message) = message.downcast_ref() {
             self.character
                 .on_weapon_message(weapon_message, &mut ctx.scene.graph);
@@ -1246,20 +2100,27 @@ impl ScriptTrait for Player {
         let game = game_mut(ctx.plugins);
         game.weapon_display.sync_to_model(self, &ctx.scene.graph);
         game.journal_display.update(ctx.dt, &self.journal);
+        if ctx.scene.graph[self.inventory_display].visibility() {
+            game.inventory_interface
+                .sync_equipment(&self.equipment, &DEFINITION.equipment);
+        }
 
         let game = game_ref(ctx.plugins);
         let level = current_level_ref(ctx.plugins).unwrap();
 
+        // Exhausted player falls back to walking.
+        let run = self.controller.run && self.can_run();
+
         self.target_local_velocity = Vector2::default();
         if self.controller.walk_forward
             || (!self.controller.aim && (self.controller.walk_left || self.controller.walk_right))
         {
-            self.target_local_velocity.y = if self.controller.run { 1.0 } else { 0.5 };
+            self.target_local_velocity.y = if run { 1.0 } else { 0.5 };
         }
         if self.controller.walk_backward {
             self.target_local_velocity.y = if self.controller.aim {
                 -1.0
-            } else if self.controller.run {
+            } else if run {
                 1.0
             } else {
                 0.5
@@ -1300,6 +2161,8 @@ impl ScriptTrait for Player {
         }
 
         self.update_health_cylinder(ctx.scene);
+        self.update_stamina_cylinder(ctx.scene);
+        self.update_armor_cylinder(ctx.scene);
 
         let has_ground_contact = self.has_ground_contact(&ctx.scene.graph);
         let is_walking = self.is_walking();
@@ -1323,11 +2186,22 @@ impl ScriptTrait for Player {
             }
             self.run_factor += (self.target_run_factor - self.run_factor) * 0.1;
 
+            if is_running && is_walking {
+                self.character.drain_stamina(&DEFINITION.stamina, ctx.dt);
+            } else {
+                self.character
+                    .regenerate_stamina(&DEFINITION.stamina, ctx.dt);
+            }
+
+            self.character.update_status_effects(ctx.dt);
+            self.update_equipment(&mut ctx.scene.graph, ctx.dt);
+
             let can_move = self.can_move(&ctx.scene.graph);
             self.update_velocity(ctx.scene, ctx.dt);
             self.handle_weapon_grab_signal(ctx.scene, ctx.handle, ctx.message_sender);
             self.handle_put_back_weapon_end_signal(ctx.scene);
             self.handle_toss_grenade_signal(Default::default(), ctx.scene, ctx.resource_manager);
+            self.update_grenade_trajectory(&mut ctx.scene.graph, ctx.dt);
 
             if self.controller.aim {
                 self.spine_pitch.set_target(self.controller.pitch);
@@ -1423,6 +2297,16 @@ impl ScriptTrait for Player {
                 self.in_air_time += ctx.dt;
             }
 
+            if self.character.handle_landing(
+                &ctx.scene.graph,
+                has_ground_contact,
+                &DEFINITION.fall_damage,
+                ctx.handle,
+                ctx.message_sender,
+            ) {
+                self.hard_landing = true;
+            }
+
             if !has_ground_contact {
                 for &land_animation in &[
                     self.state_machine.land_animation,
@@ -1440,7 +2324,26 @@ impl ScriptTrait for Player {
 
             self.check_doors(ctx.scene, &level.doors_container);
             self.check_elevators(ctx.scene, &level.elevators);
+            self.check_workbenches(
+                ctx.scene,
+                &level.workbenches,
+                &level.sound_manager,
+                &game.message_sender,
+            );
+            self.check_containers(
+                ctx.scene,
+                &level.containers,
+                &level.sound_manager,
+                &game.message_sender,
+            );
             self.update_shooting(ctx.scene, ctx.dt, ctx.elapsed_time, ctx.message_sender);
+            self.update_melee(
+                ctx.scene,
+                ctx.handle,
+                &level.actors,
+                ctx.message_sender,
+                ctx.dt,
+            );
             self.check_items(
                 game_mut(ctx.plugins),
                 ctx.scene,
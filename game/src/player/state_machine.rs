@@ -2,10 +2,14 @@ use crate::{character::Character, sound::SoundManager, utils};
 use fyrox::core::algebra::Vector2;
 use fyrox::{
     animation::{
-        machine::{MachineLayer, Parameter, State, Transition},
-        Animation,
+        machine::{MachineLayer, Parameter, PoseNode, State, Transition},
+        Animation, AnimationSignal,
+    },
+    core::{
+        algebra::Vector3,
+        pool::Handle,
+        uuid::{uuid, Uuid},
     },
-    core::{algebra::Vector3, pool::Handle},
     scene::{
         animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
         graph::Graph,
@@ -28,6 +32,8 @@ pub struct StateMachineInput<'a> {
     pub has_ground_contact: bool,
     pub is_aiming: bool,
     pub toss_grenade: bool,
+    pub melee: bool,
+    pub reload: bool,
     pub weapon_kind: CombatWeaponKind,
     pub change_weapon: bool,
     pub is_dead: bool,
@@ -37,6 +43,21 @@ pub struct StateMachineInput<'a> {
     pub local_velocity: Vector2<f32>,
 }
 
+/// An action of the upper body that is created at runtime if a prefab does not have it.
+struct ActionDefinition {
+    animation: &'static str,
+    /// An animation that is copied to make the animation of the action.
+    source_animation: &'static str,
+    speed: f32,
+    signal: &'static str,
+    signal_id: Uuid,
+    /// Normalized time of the animation at which the signal is emitted.
+    signal_time: f32,
+    state: &'static str,
+    enter_rule: &'static str,
+    exit_rule: &'static str,
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct StateMachine {
     pub machine_handle: Handle<Node>,
@@ -55,6 +76,12 @@ pub struct StateMachine {
     pub aim_state: Handle<State>,
     pub toss_grenade_state: Handle<State>,
     pub put_back_state: Handle<State>,
+    /// Melee and reload states and animations are created at runtime if the machine was authored
+    /// without them.
+    pub melee_state: Handle<State>,
+    pub melee_animation: Handle<Animation>,
+    pub reload_state: Handle<State>,
+    pub reload_animation: Handle<Animation>,
     pub toss_grenade_animation: Handle<Animation>,
     pub put_back_animation: Handle<Animation>,
     pub grab_animation: Handle<Animation>,
@@ -66,11 +93,46 @@ impl StateMachine {
     pub const GRAB_WEAPON_SIGNAL: &'static str = "Grab";
     pub const PUT_BACK_WEAPON_END_SIGNAL: &'static str = "PutBack";
     pub const TOSS_GRENADE_SIGNAL: &'static str = "TossGrenade";
+    pub const MELEE_HIT_SIGNAL: &'static str = "MeleeHit";
+    pub const RELOAD_SIGNAL: &'static str = "Reloaded";
+
+    const MELEE: ActionDefinition = ActionDefinition {
+        animation: "agent_melee",
+        source_animation: "agent_toss_grenade",
+        speed: 1.5,
+        signal: Self::MELEE_HIT_SIGNAL,
+        signal_id: uuid!("3c0f5a4e-9b7d-4e62-8f15-6a2d1c7e94b0"),
+        signal_time: 0.4,
+        state: "Melee",
+        enter_rule: "Melee",
+        exit_rule: "MeleeFinished",
+    };
+
+    // Speed of the reload animation is set when a reload begins, so it matches reload time of the
+    // weapon.
+    const RELOAD: ActionDefinition = ActionDefinition {
+        animation: "agent_reload",
+        source_animation: "agent_put_back",
+        speed: 1.0,
+        signal: Self::RELOAD_SIGNAL,
+        signal_id: uuid!("b6a1e0d3-2f84-4c57-9e3a-71d5c8f02b46"),
+        signal_time: 0.9,
+        state: "Reload",
+        enter_rule: "Reload",
+        exit_rule: "ReloadFinished",
+    };
+
+    // States of the upper body layer from which actions can be started.
+    const ACTION_SOURCE_STATES: [&'static str; 7] =
+        ["Aim", "Idle", "Walk", "Run", "Jump", "Fall", "Land"];
 
     const LOWER_BODY_LAYER_INDEX: usize = 0;
     const UPPER_BODY_LAYER_INDEX: usize = 1;
 
-    pub fn new(machine_handle: Handle<Node>, graph: &Graph) -> Option<Self> {
+    pub fn new(machine_handle: Handle<Node>, graph: &mut Graph) -> Option<Self> {
+        Self::create_action_state(machine_handle, graph, &Self::MELEE)?;
+        Self::create_action_state(machine_handle, graph, &Self::RELOAD)?;
+
         let absm = graph.try_get_of_type::<AnimationBlendingStateMachine>(machine_handle)?;
 
         let animation_player = graph.try_get_of_type::<AnimationPlayer>(absm.animation_player())?;
@@ -103,12 +165,95 @@ impl StateMachine {
             aim_state: upper_body.find_state_by_name_ref("Aim")?.0,
             toss_grenade_state: upper_body.find_state_by_name_ref("TossGrenade")?.0,
             put_back_state: upper_body.find_state_by_name_ref("PutBack")?.0,
+            melee_state: upper_body.find_state_by_name_ref("Melee")?.0,
+            melee_animation: animations.find_by_name_ref("agent_melee")?.0,
+            reload_state: upper_body.find_state_by_name_ref("Reload")?.0,
+            reload_animation: animations.find_by_name_ref("agent_reload")?.0,
             toss_grenade_animation: animations.find_by_name_ref("agent_toss_grenade")?.0,
             put_back_animation: animations.find_by_name_ref("agent_put_back")?.0,
             grab_animation: animations.find_by_name_ref("agent_grab")?.0,
         })
     }
 
+    /// Character prefabs do not have animations for some actions, so they are made from similar
+    /// animations and played by new states of the upper body layer. The machine is saved with the
+    /// scene, so states and animations are created only once.
+    fn create_action_state(
+        machine_handle: Handle<Node>,
+        graph: &mut Graph,
+        action: &ActionDefinition,
+    ) -> Option<()> {
+        let absm = graph.try_get_of_type::<AnimationBlendingStateMachine>(machine_handle)?;
+        let animation_player_handle = absm.animation_player();
+        let has_state = absm
+            .machine()
+            .layers()
+            .get(Self::UPPER_BODY_LAYER_INDEX)?
+            .find_state_by_name_ref(action.state)
+            .is_some();
+
+        let animations = utils::fetch_animation_container_mut(graph, animation_player_handle);
+        let animation = match animations.find_by_name_ref(action.animation) {
+            Some((handle, _)) => handle,
+            None => {
+                let (_, source) = animations.find_by_name_ref(action.source_animation)?;
+                let mut animation = source.clone();
+                // Signals of the source animation belong to another action.
+                for signal in animation.signals_mut() {
+                    signal.enabled = false;
+                }
+                let signal_time = animation.length() * action.signal_time;
+                animation.set_name(action.animation);
+                animation
+                    .set_enabled(false)
+                    .set_loop(false)
+                    .set_speed(action.speed)
+                    .add_signal(AnimationSignal {
+                        id: action.signal_id,
+                        name: action.signal.to_string(),
+                        time: signal_time,
+                        enabled: true,
+                    });
+                animations.add(animation)
+            }
+        };
+
+        if !has_state {
+            let upper_body = graph
+                .try_get_mut_of_type::<AnimationBlendingStateMachine>(machine_handle)?
+                .machine_mut()
+                .layers_mut()
+                .get_mut(Self::UPPER_BODY_LAYER_INDEX)?;
+
+            let aim_state = upper_body.find_state_by_name_ref("Aim")?.0;
+            let node = upper_body.add_node(PoseNode::make_play_animation(animation));
+            let state = upper_body.add_state(State::new(action.state, node));
+
+            // The action could be started while aiming or moving, it returns to aiming when done
+            // and the aim state switches to the right locomotion state by itself.
+            for source_name in Self::ACTION_SOURCE_STATES {
+                if let Some((source, _)) = upper_body.find_state_by_name_ref(source_name) {
+                    upper_body.add_transition(Transition::new(
+                        &format!("{}To{}", source_name, action.state),
+                        source,
+                        state,
+                        0.1,
+                        action.enter_rule,
+                    ));
+                }
+            }
+            upper_body.add_transition(Transition::new(
+                &format!("{}ToAim", action.state),
+                state,
+                aim_state,
+                0.2,
+                action.exit_rule,
+            ));
+        }
+
+        Some(())
+    }
+
     pub fn fetch_layer<'a>(&self, graph: &'a Graph, idx: usize) -> Option<&'a MachineLayer> {
         graph
             .try_get_of_type::<AnimationBlendingStateMachine>(self.machine_handle)
@@ -209,6 +354,8 @@ impl StateMachine {
             has_ground_contact,
             is_aiming,
             toss_grenade,
+            melee,
+            reload,
             weapon_kind,
             change_weapon,
             is_dead,
@@ -244,6 +391,8 @@ impl StateMachine {
         let toss_grenade_animation_ended = animations_container
             .get(self.toss_grenade_animation)
             .has_ended();
+        let melee_animation_ended = animations_container.get(self.melee_animation).has_ended();
+        let reload_animation_ended = animations_container.get(self.reload_animation).has_ended();
 
         scene
             .graph
@@ -265,6 +414,10 @@ impl StateMachine {
             .set_parameter("WalkFactor", Parameter::Weight(1.0 - run_factor))
             .set_parameter("RunFactor", Parameter::Weight(run_factor))
             .set_parameter("TossGrenade", Parameter::Rule(toss_grenade))
+            .set_parameter("Melee", Parameter::Rule(melee))
+            .set_parameter("MeleeFinished", Parameter::Rule(melee_animation_ended))
+            .set_parameter("Reload", Parameter::Rule(reload))
+            .set_parameter("ReloadFinished", Parameter::Rule(reload_animation_ended))
             .set_parameter("ReactToHit", Parameter::Rule(should_be_stunned))
             .set_parameter("GrabWeapon", Parameter::Rule(put_back_animation_ended))
             .set_parameter("RemoveWeapon", Parameter::Rule(change_weapon))
@@ -285,6 +438,14 @@ impl StateMachine {
         ]
     }
 
+    pub fn is_melee_in_progress(&self, scene: &Scene, animation_player: Handle<Node>) -> bool {
+        let animations_container =
+            utils::fetch_animation_container_ref(&scene.graph, animation_player);
+
+        let melee_animation = &animations_container[self.melee_animation];
+        !melee_animation.has_ended() && melee_animation.is_enabled()
+    }
+
     pub fn is_stunned(&self, scene: &Scene, animation_player: Handle<Node>) -> bool {
         let animations_container =
             utils::fetch_animation_container_ref(&scene.graph, animation_player);
@@ -0,0 +1,193 @@
+//! Physics-driven pose of a dead character, built from hit boxes of the character.
+
+use crate::character::HitBox;
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::BaseBuilder,
+        collider::Collider,
+        graph::Graph,
+        joint::{BallJoint, JointBuilder, JointParams},
+        node::Node,
+        rigidbody::{RigidBody, RigidBodyType},
+        transform::TransformBuilder,
+    },
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct RagdollDefinition {
+    /// Time (in seconds) that is needed to fully blend from animated pose to physical.
+    pub blend_time: f32,
+    /// Initial velocity of body parts in the direction of the killing hit.
+    pub impulse: f32,
+}
+
+#[derive(Default, Debug, Clone, Visit)]
+struct RagdollLimb {
+    bone: Handle<Node>,
+    body: Handle<Node>,
+    // Transform of the bone in the local coordinates of the body.
+    rotation_offset: UnitQuaternion<f32>,
+    position_offset: Vector3<f32>,
+}
+
+#[derive(Default, Debug, Clone, Visit)]
+pub struct Ragdoll {
+    limbs: Vec<RagdollLimb>,
+    blend_factor: f32,
+    active: bool,
+}
+
+impl Ragdoll {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns `true` if the ragdoll is active and the pose is fully controlled by physics.
+    pub fn is_settled(&self) -> bool {
+        self.active && self.blend_factor >= 1.0
+    }
+
+    /// Turns rigid bodies of hit boxes into dynamic ones, connects them with joints and pushes
+    /// them with the given velocity. Bodies are re-attached to the `root` node, so they will be
+    /// removed together with the character.
+    pub fn activate(
+        &mut self,
+        graph: &mut Graph,
+        root: Handle<Node>,
+        hit_boxes: &[HitBox],
+        capsule_collider: Handle<Node>,
+        velocity: Vector3<f32>,
+    ) {
+        if self.active {
+            return;
+        }
+
+        self.active = true;
+        self.blend_factor = 0.0;
+        self.limbs.clear();
+
+        for hit_box in hit_boxes {
+            let body = graph[hit_box.collider].parent();
+            if graph.try_get_of_type::<RigidBody>(body).is_none()
+                || self.limbs.iter().any(|l| l.body == body)
+            {
+                continue;
+            }
+
+            let (body_rotation, body_position) = graph.global_rotation_position_no_scale(body);
+            let (bone_rotation, bone_position) =
+                graph.global_rotation_position_no_scale(hit_box.bone);
+            let inv_body_rotation = body_rotation.inverse();
+
+            self.limbs.push(RagdollLimb {
+                bone: hit_box.bone,
+                body,
+                rotation_offset: inv_body_rotation * bone_rotation,
+                position_offset: inv_body_rotation * (bone_position - body_position),
+            });
+        }
+
+        let (root_rotation, _) = graph.global_rotation_position_no_scale(root);
+        let inv_root_transform = graph[root]
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_default();
+
+        // Connect each body with the body of the closest parent bone that has one.
+        for limb in self.limbs.iter() {
+            let mut parent = graph[limb.bone].parent();
+            while parent.is_some() {
+                if let Some(parent_limb) = self.limbs.iter().find(|l| l.bone == parent) {
+                    let joint_position = inv_root_transform
+                        .transform_point(&graph[limb.bone].global_position().into())
+                        .coords;
+
+                    let joint = JointBuilder::new(
+                        BaseBuilder::new().with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(joint_position)
+                                .build(),
+                        ),
+                    )
+                    .with_params(JointParams::BallJoint(BallJoint::default()))
+                    .with_body1(parent_limb.body)
+                    .with_body2(limb.body)
+                    .with_contacts_enabled(false)
+                    .build(graph);
+
+                    graph.link_nodes(joint, root);
+
+                    break;
+                }
+                parent = graph[parent].parent();
+            }
+        }
+
+        for limb in self.limbs.iter() {
+            let (body_rotation, body_position) = graph.global_rotation_position_no_scale(limb.body);
+
+            // Bodies must not follow the bones anymore, it is the other way around now.
+            graph.link_nodes(limb.body, root);
+
+            let body = graph[limb.body].as_rigid_body_mut();
+            body.local_transform_mut()
+                .set_position(
+                    inv_root_transform
+                        .transform_point(&body_position.into())
+                        .coords,
+                )
+                .set_rotation(root_rotation.inverse() * body_rotation)
+                .set_scale(Vector3::new(1.0, 1.0, 1.0));
+            body.set_body_type(RigidBodyType::Dynamic);
+            body.set_lin_vel(velocity);
+        }
+
+        // The capsule must not push the body parts away.
+        if let Some(capsule) = graph.try_get_mut_of_type::<Collider>(capsule_collider) {
+            capsule.set_is_sensor(true);
+        }
+    }
+
+    /// Blends animated pose of the bones with the pose of the bodies. Must be called after
+    /// animations are applied.
+    pub fn update(&mut self, graph: &mut Graph, blend_time: f32, dt: f32) {
+        if !self.active {
+            return;
+        }
+
+        self.blend_factor = (self.blend_factor + dt / blend_time.max(f32::EPSILON)).min(1.0);
+
+        for limb in self.limbs.iter() {
+            let (body_rotation, body_position) = graph.global_rotation_position_no_scale(limb.body);
+            let bone_rotation = body_rotation * limb.rotation_offset;
+            let bone_position = body_position + body_rotation * limb.position_offset;
+
+            let parent = graph[limb.bone].parent();
+            let (parent_rotation, _) = graph.global_rotation_position_no_scale(parent);
+            let inv_parent_transform = graph[parent]
+                .global_transform()
+                .try_inverse()
+                .unwrap_or_default();
+
+            let physical_rotation = parent_rotation.inverse() * bone_rotation;
+            let physical_position = inv_parent_transform
+                .transform_point(&bone_position.into())
+                .coords;
+
+            let transform = graph[limb.bone].local_transform_mut();
+            let rotation = transform
+                .rotation()
+                .slerp(&physical_rotation, self.blend_factor);
+            let position = transform
+                .position()
+                .lerp(&physical_position, self.blend_factor);
+            transform.set_rotation(rotation).set_position(position);
+        }
+    }
+}
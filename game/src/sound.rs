@@ -1,5 +1,6 @@
+use crate::config::SoundConfig;
 use fyrox::scene::sound::reverb::Reverb;
-use fyrox::scene::sound::{Effect, SoundBufferResource};
+use fyrox::scene::sound::{AudioBus, Effect, Sound, SoundBufferResource, SoundContext};
 use fyrox::{
     core::{
         algebra::Vector3, futures::executor::block_on, pool::Handle, sstorage::ImmutableString,
@@ -43,10 +44,94 @@ pub enum SoundKind {
     FootStep,
 }
 
+/// Every category of sounds is played through its own audio bus, so it has its own volume.
+#[derive(Deserialize, Hash, Eq, PartialEq, Copy, Clone, Debug)]
+pub enum SoundCategory {
+    Sfx,
+    Voice,
+    Ui,
+    Ambience,
+    Music,
+}
+
+impl Default for SoundCategory {
+    fn default() -> Self {
+        Self::Sfx
+    }
+}
+
+impl SoundCategory {
+    pub const ALL: [SoundCategory; 5] = [
+        SoundCategory::Sfx,
+        SoundCategory::Voice,
+        SoundCategory::Ui,
+        SoundCategory::Ambience,
+        SoundCategory::Music,
+    ];
+
+    pub fn bus_name(self) -> &'static str {
+        match self {
+            SoundCategory::Sfx => "Sfx",
+            SoundCategory::Voice => "Voice",
+            SoundCategory::Ui => "Ui",
+            SoundCategory::Ambience => "Ambience",
+            SoundCategory::Music => "Music",
+        }
+    }
+}
+
+/// Adds an audio bus for every sound category to the sound context, buses are children of the
+/// primary bus. Does nothing for buses that already exist.
+pub fn create_audio_buses(sound_context: &SoundContext) {
+    let mut state = sound_context.state();
+    let bus_graph = state.bus_graph_mut();
+    let primary_bus = bus_graph.primary_bus_handle();
+
+    for category in SoundCategory::ALL {
+        if !bus_graph
+            .buses_iter()
+            .any(|bus| bus.name() == category.bus_name())
+        {
+            bus_graph.add_bus(AudioBus::new(category.bus_name().to_owned()), primary_bus);
+        }
+    }
+}
+
+/// Applies volumes from the config to the primary bus and the buses of sound categories.
+pub fn apply_bus_volumes(sound_context: &SoundContext, sound_config: &SoundConfig) {
+    let mut state = sound_context.state();
+    let bus_graph = state.bus_graph_mut();
+
+    bus_graph
+        .primary_bus_mut()
+        .set_gain(sound_config.master_volume);
+
+    for bus in bus_graph.buses_iter_mut() {
+        if let Some(category) = SoundCategory::ALL
+            .into_iter()
+            .find(|c| c.bus_name() == bus.name())
+        {
+            bus.set_gain(sound_config.volume(category));
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Default)]
 pub struct SoundBase {
     material_to_sound: HashMap<MaterialType, HashMap<SoundKind, Vec<PathBuf>>>,
     texture_to_material: HashMap<PathBuf, MaterialType>,
+    /// Sounds that are not listed here are played through the `Sfx` bus.
+    #[serde(default)]
+    sound_to_category: HashMap<PathBuf, SoundCategory>,
+}
+
+impl SoundBase {
+    pub fn category_of(&self, path: &Path) -> SoundCategory {
+        self.sound_to_category
+            .get(path)
+            .cloned()
+            .unwrap_or_default()
+    }
 }
 
 impl SoundBase {
@@ -193,8 +278,22 @@ impl SoundManager {
             .primary_bus_mut()
             .add_effect(Effect::Reverb(reverb));
 
+        create_audio_buses(&scene.graph.sound_context);
+
         let sound_base = SoundBase::load();
 
+        // Sounds that are placed on the level (ambient noises, etc.) use buses of their categories
+        // too.
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(sound) = node.cast_mut::<Sound>() {
+                let category = sound
+                    .buffer()
+                    .map(|buffer| sound_base.category_of(buffer.state().path()))
+                    .unwrap_or_default();
+                sound.set_audio_bus(category.bus_name().to_owned());
+            }
+        }
+
         Self {
             sound_map: SoundMap::new(scene, &sound_base),
             sound_base,
@@ -219,6 +318,12 @@ impl SoundManager {
             ),
         )
         .with_buffer(buffer.clone().into())
+        .with_audio_bus(
+            self.sound_base
+                .category_of(buffer.state().path())
+                .bus_name()
+                .to_owned(),
+        )
         .with_status(Status::Playing)
         .with_play_once(true)
         .with_gain(gain)
@@ -251,18 +356,10 @@ impl SoundManager {
         }
     }
 
-    pub fn play_environment_sound(
-        &self,
-        graph: &mut Graph,
-        collider: Handle<Node>,
-        feature: FeatureId,
-        position: Vector3<f32>,
-        sound_kind: SoundKind,
-        gain: f32,
-        rolloff_factor: f32,
-        radius: f32,
-    ) {
-        let material = self.sound_map.ranges_of(collider).and_then(|ranges| {
+    /// Returns a type of material of the given feature of a collider, if the collider has
+    /// respective mapping in the sound map.
+    pub fn material_of(&self, collider: Handle<Node>, feature: FeatureId) -> Option<MaterialType> {
+        self.sound_map.ranges_of(collider).and_then(|ranges| {
             match feature {
                 FeatureId::Face(idx) => {
                     let mut material = None;
@@ -281,7 +378,21 @@ impl SoundManager {
                     ranges.first().map(|first_range| first_range.material)
                 }
             }
-        });
+        })
+    }
+
+    pub fn play_environment_sound(
+        &self,
+        graph: &mut Graph,
+        collider: Handle<Node>,
+        feature: FeatureId,
+        position: Vector3<f32>,
+        sound_kind: SoundKind,
+        gain: f32,
+        rolloff_factor: f32,
+        radius: f32,
+    ) {
+        let material = self.material_of(collider, feature);
 
         if let Some(material) = material {
             if let Some(map) = self.sound_base.material_to_sound.get(&material) {
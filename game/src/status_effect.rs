@@ -0,0 +1,214 @@
+//! Lingering effects (bleeding, burning, etc.) that damage and hinder characters over time.
+
+use crate::{
+    character::Resistances, level::item::ItemKind, utils::is_probability_event_occurred,
+    weapon::projectile::DamageType,
+};
+use fyrox::{
+    core::{reflect::prelude::*, visitor::prelude::*},
+    lazy_static::lazy_static,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Deserialize,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum StatusEffectKind {
+    Bleeding,
+    Burning,
+    Infection,
+    Stun,
+    Stasis,
+}
+
+impl Default for StatusEffectKind {
+    fn default() -> Self {
+        Self::Bleeding
+    }
+}
+
+impl StatusEffectKind {
+    pub fn definition(&self) -> &'static StatusEffectDefinition {
+        DEFINITIONS.map.get(self).unwrap()
+    }
+}
+
+/// Defines what happens when an effect is applied to a character that already has it.
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub enum StackingRule {
+    /// Restarts the timer of the effect.
+    Refresh,
+    /// Adds a stack (up to `max_stacks`) and restarts the timer. Damage is multiplied by the
+    /// amount of stacks.
+    Stack,
+    /// Prolongs the effect, total duration is limited by `duration * max_stacks`.
+    Extend,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StatusEffectDefinition {
+    pub duration: f32,
+    pub damage_per_second: f32,
+    /// Type of the damage dealt by the effect, if any. Resistances of the character are applied
+    /// to the damage of this type.
+    pub damage_type: Option<DamageType>,
+    pub stacking: StackingRule,
+    pub max_stacks: u32,
+    /// Movement speed of an affected character is multiplied by this value.
+    pub movement_factor: f32,
+    /// Weapon recoil of an affected character is multiplied by this value.
+    pub recoil_factor: f32,
+    /// Damage types that could cause the effect along with probabilities of it.
+    pub caused_by: Vec<(DamageType, f32)>,
+    /// Items that cure the effect when used.
+    pub cured_by: Vec<ItemKind>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct StatusEffectDefinitionContainer {
+    map: HashMap<StatusEffectKind, StatusEffectDefinition>,
+}
+
+impl StatusEffectDefinitionContainer {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/status_effects.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITIONS: StatusEffectDefinitionContainer =
+        StatusEffectDefinitionContainer::new();
+}
+
+#[derive(Default, Debug, Clone, Visit, Reflect)]
+pub struct StatusEffect {
+    kind: StatusEffectKind,
+    time_left: f32,
+    stacks: u32,
+}
+
+impl StatusEffect {
+    pub fn kind(&self) -> StatusEffectKind {
+        self.kind
+    }
+
+    pub fn time_left(&self) -> f32 {
+        self.time_left
+    }
+
+    pub fn stacks(&self) -> u32 {
+        self.stacks
+    }
+}
+
+#[derive(Default, Debug, Clone, Visit, Reflect)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, kind: StatusEffectKind) {
+        let definition = kind.definition();
+
+        if let Some(effect) = self.effects.iter_mut().find(|e| e.kind == kind) {
+            match definition.stacking {
+                StackingRule::Refresh => {
+                    effect.time_left = definition.duration;
+                }
+                StackingRule::Stack => {
+                    effect.stacks = (effect.stacks + 1).min(definition.max_stacks.max(1));
+                    effect.time_left = definition.duration;
+                }
+                StackingRule::Extend => {
+                    effect.time_left = (effect.time_left + definition.duration)
+                        .min(definition.duration * definition.max_stacks.max(1) as f32);
+                }
+            }
+        } else {
+            self.effects.push(StatusEffect {
+                kind,
+                time_left: definition.duration,
+                stacks: 1,
+            });
+        }
+    }
+
+    /// Rolls a chance for each effect that could be caused by the given type of damage.
+    pub fn apply_caused_by(&mut self, damage_type: DamageType) {
+        for (&kind, definition) in DEFINITIONS.map.iter() {
+            for &(cause, probability) in definition.caused_by.iter() {
+                if cause == damage_type && is_probability_event_occurred(probability) {
+                    self.apply(kind);
+                }
+            }
+        }
+    }
+
+    /// Removes every effect that could be cured by the item. Returns `true` if anything was cured.
+    pub fn cure_with(&mut self, item: ItemKind) -> bool {
+        let count = self.effects.len();
+        self.effects
+            .retain(|e| !e.kind.definition().cured_by.contains(&item));
+        count != self.effects.len()
+    }
+
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    pub fn movement_factor(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| e.kind.definition().movement_factor)
+            .product()
+    }
+
+    pub fn recoil_factor(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| e.kind.definition().recoil_factor)
+            .product()
+    }
+
+    /// Advances timers of the effects and removes expired ones. Returns amount of damage dealt by
+    /// the effects during the given time interval.
+    pub fn update(&mut self, resistances: &Resistances, dt: f32) -> f32 {
+        let mut damage = 0.0;
+
+        for effect in self.effects.iter_mut() {
+            let definition = effect.kind.definition();
+
+            let time = dt.min(effect.time_left.max(0.0));
+            damage += definition.damage_per_second
+                * effect.stacks as f32
+                * time
+                * definition
+                    .damage_type
+                    .map_or(1.0, |kind| resistances.damage_factor(kind));
+
+            effect.time_left -= dt;
+        }
+
+        self.effects.retain(|e| e.time_left > 0.0);
+
+        damage
+    }
+}
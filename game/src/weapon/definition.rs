@@ -1,10 +1,12 @@
-use crate::level::item::ItemKind;
+use crate::{level::item::ItemKind, weapon::projectile::Damage};
 use fyrox::{
     core::{reflect::prelude::*, visitor::prelude::*},
     lazy_static::lazy_static,
+    utils::log::Log,
 };
 use serde::Deserialize;
-use std::{collections::HashMap, fs::File};
+use std::{collections::HashMap, fs::File, str::FromStr};
+use strum::VariantNames;
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 #[derive(
@@ -48,9 +50,170 @@ impl WeaponKind {
     }
 }
 
+/// Parameters of a weapon bash (a melee attack made with a weapon in hands).
+#[derive(Deserialize, Debug, Clone)]
+pub struct MeleeDefinition {
+    pub damage: f32,
+    /// Minimum time (in seconds) between two consecutive attacks.
+    pub cooldown: f32,
+    /// Maximum distance (in meters) at which the attack could hit anyone.
+    pub range: f32,
+}
+
+impl MeleeDefinition {
+    /// Parameters of the melee attack when a character does not have a weapon in hands.
+    pub const UNARMED: Self = Self {
+        damage: 10.0,
+        cooldown: 0.6,
+        range: 1.0,
+    };
+}
+
+#[derive(Deserialize, Copy, Clone, Debug, PartialEq, AsRefStr)]
+pub enum FireMode {
+    /// A single shot per trigger pull.
+    Semi,
+    /// Given amount of shots per trigger pull.
+    Burst(u32),
+    /// Shoots while the trigger is held.
+    Auto,
+    /// The trigger must be held for the given time (in seconds), the shot is made on release.
+    Charged(f32),
+}
+
+/// Parameters of the accuracy model. All angles are in degrees, the spread is the angle of the
+/// cone in which projectiles are fired.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SpreadDefinition {
+    /// Spread of the weapon in hands of a character that stands still.
+    pub base: f32,
+    pub max: f32,
+    /// Every shot increases the spread by this value.
+    pub per_shot: f32,
+    /// Speed (in degrees per second) at which the spread caused by shots goes away.
+    pub recovery: f32,
+    /// Additional spread when an owner of the weapon is moving.
+    pub moving: f32,
+    /// Additional spread when an owner of the weapon is running.
+    pub running: f32,
+    /// The spread is multiplied by this value when an owner of the weapon is aiming.
+    pub aiming_factor: f32,
+}
+
+impl Default for SpreadDefinition {
+    fn default() -> Self {
+        Self {
+            base: 0.5,
+            max: 5.0,
+            per_shot: 0.5,
+            recovery: 4.0,
+            moving: 1.0,
+            running: 3.0,
+            aiming_factor: 0.5,
+        }
+    }
+}
+
+/// Parameters of upgrades that could be made on a work bench. Every stat could be upgraded up to
+/// `max_level` times, each level gives the specified bonus.
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpgradeDefinition {
+    pub max_level: u32,
+    /// Amount of upgrade components needed for a single level of any stat.
+    pub cost: u32,
+    /// Fraction of the base damage that is added per level.
+    pub damage: f32,
+    /// Amount of ammo (in inventory units) that is added to the magazine capacity per level.
+    pub magazine: u32,
+    /// Fraction of the base reload speed that is added per level.
+    pub reload_speed: f32,
+    /// Fraction of the base fire rate that is added per level.
+    pub fire_rate: f32,
+}
+
+impl Default for UpgradeDefinition {
+    fn default() -> Self {
+        Self {
+            max_level: 3,
+            cost: 1,
+            damage: 0.1,
+            magazine: 4,
+            reload_speed: 0.1,
+            fire_rate: 0.1,
+        }
+    }
+}
+
+fn default_fire_modes() -> Vec<FireMode> {
+    vec![FireMode::Semi]
+}
+
+fn default_melee() -> MeleeDefinition {
+    MeleeDefinition::UNARMED
+}
+
+/// Parameters of a weapon. Optional parameters could be omitted in the config, a missing required
+/// parameter makes the whole config invalid.
 #[derive(Deserialize, Debug)]
 pub struct WeaponDefinition {
     pub model: String,
+    /// A prefab with `Projectile` script that is spawned on every shot.
+    pub projectile: String,
+    /// Damage of a single projectile, overrides the damage set in the projectile prefab.
+    pub damage: Damage,
+    /// Amount of obstacles a projectile could pass through, overrides the value set in the
+    /// projectile prefab.
+    #[serde(default)]
+    pub penetration: u32,
+    /// Amount of ricochets off metal surfaces, overrides the value set in the projectile prefab.
+    #[serde(default)]
+    pub ricochets: u32,
+    /// Minimum time (in seconds) between two consecutive shots.
+    pub shoot_interval: f32,
+    pub ammo_consumption_per_shot: u32,
+    /// Available fire modes, the first one is used by default.
+    #[serde(default = "default_fire_modes")]
+    pub fire_modes: Vec<FireMode>,
+    #[serde(default)]
+    pub spread: SpreadDefinition,
+    /// Range (in degrees) of random vertical recoil.
+    pub v_recoil: (f32, f32),
+    /// Range (in degrees) of random horizontal recoil.
+    pub h_recoil: (f32, f32),
+    /// Amount of ammo (in inventory units) a magazine could hold.
+    pub magazine_capacity: u32,
+    /// Time (in seconds) that is needed to refill the magazine.
+    pub reload_time: f32,
+    /// A random sound from the list is played on every shot.
+    #[serde(default)]
+    pub shot_sounds: Vec<String>,
+    #[serde(default = "default_melee")]
+    pub melee: MeleeDefinition,
+    #[serde(default)]
+    pub upgrades: UpgradeDefinition,
+}
+
+impl Default for WeaponDefinition {
+    fn default() -> Self {
+        Self {
+            model: Default::default(),
+            projectile: Default::default(),
+            damage: Default::default(),
+            penetration: 0,
+            ricochets: 0,
+            shoot_interval: 0.15,
+            ammo_consumption_per_shot: 2,
+            fire_modes: default_fire_modes(),
+            spread: Default::default(),
+            v_recoil: (-2.0, 4.0),
+            h_recoil: (-1.0, 1.0),
+            magazine_capacity: 30,
+            reload_time: 2.0,
+            shot_sounds: Default::default(),
+            melee: default_melee(),
+            upgrades: Default::default(),
+        }
+    }
 }
 
 #[derive(Deserialize, Default)]
@@ -59,12 +222,64 @@ pub struct WeaponDefinitionContainer {
 }
 
 impl WeaponDefinitionContainer {
+    const PATH: &'static str = "data/configs/weapons.ron";
+
     pub fn new() -> Self {
-        let file = File::open("data/configs/weapons.ron").unwrap();
-        ron::de::from_reader(file).unwrap()
+        let container = match File::open(Self::PATH)
+            .map_err(|e| e.to_string())
+            .and_then(|file| ron::de::from_reader(file).map_err(|e| e.to_string()))
+        {
+            Ok(container) => container,
+            Err(error) => {
+                Log::err(format!(
+                    "[Weapons]: Unable to load {}! Reason: {}",
+                    Self::PATH,
+                    error
+                ));
+                Self::default()
+            }
+        };
+
+        if !container.validate() {
+            Log::warn(format!(
+                "[Weapons]: {} is incomplete, weapons without a definition use default parameters!",
+                Self::PATH
+            ));
+        }
+
+        container
+    }
+
+    /// Reports every weapon kind that does not have a definition. Such weapons will use the
+    /// default parameters. Returns `true` if every weapon kind is defined.
+    #[must_use]
+    pub fn validate(&self) -> bool {
+        let mut valid = true;
+
+        for name in WeaponKind::VARIANTS {
+            if let Ok(kind) = WeaponKind::from_str(name) {
+                if !self.map.contains_key(&kind) {
+                    Log::err(format!(
+                        "[Weapons]: {} does not have a definition in {}!",
+                        name,
+                        Self::PATH
+                    ));
+                    valid = false;
+                }
+            }
+        }
+
+        valid
+    }
+
+    /// Returns a definition of the weapon of the given kind, or the default definition if there
+    /// is none.
+    pub fn get(&self, kind: WeaponKind) -> &WeaponDefinition {
+        self.map.get(&kind).unwrap_or(&FALLBACK_DEFINITION)
     }
 }
 
 lazy_static! {
     pub static ref DEFINITIONS: WeaponDefinitionContainer = WeaponDefinitionContainer::new();
+    static ref FALLBACK_DEFINITION: WeaponDefinition = WeaponDefinition::default();
 }
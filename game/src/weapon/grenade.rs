@@ -0,0 +1,107 @@
+//! Hand grenades. Every kind of grenade is a separate item, all of them share the same throwing
+//! mechanics and differ only by the effect of the explosion.
+
+use crate::{
+    level::item::ItemKind, status_effect::StatusEffectKind, weapon::projectile::DamageType,
+};
+use fyrox::{core::pool::Handle, lazy_static::lazy_static, scene::node::Node, utils::log::Log};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File};
+
+/// Disables electronics (turrets, doors) for the given time.
+pub struct EmpMessage {
+    pub target: Handle<Node>,
+    pub duration: f32,
+}
+
+/// Grenade kinds in the order they're cycled by the player.
+pub const GRENADES: [ItemKind; 4] = [
+    ItemKind::Grenade,
+    ItemKind::StasisGrenade,
+    ItemKind::IncendiaryGrenade,
+    ItemKind::EmpGrenade,
+];
+
+#[derive(Deserialize, Debug)]
+pub struct GrenadeDefinition {
+    /// A prefab with `Projectile` script, it must have splash damage.
+    pub projectile: String,
+    pub damage_type: DamageType,
+    /// Damage of the prefab is multiplied by this value.
+    pub damage_scale: f32,
+    /// An effect that is applied to every character in the radius of the explosion.
+    pub status_effect: Option<StatusEffectKind>,
+    /// Time (in seconds) for which turrets and doors in the radius of the explosion are disabled.
+    pub emp_duration: f32,
+}
+
+impl Default for GrenadeDefinition {
+    fn default() -> Self {
+        Self {
+            projectile: "data/models/grenade/grenade_proj.rgs".to_string(),
+            damage_type: DamageType::Explosive,
+            damage_scale: 1.0,
+            status_effect: None,
+            emp_duration: 0.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GrenadeDefinitionContainer {
+    /// Speed (in m/s) of a grenade that was thrown without charging.
+    pub min_throw_speed: f32,
+    pub max_throw_speed: f32,
+    /// Time (in seconds) that is needed to charge a throw to the maximum speed.
+    pub charge_time: f32,
+    pub map: HashMap<ItemKind, GrenadeDefinition>,
+}
+
+impl Default for GrenadeDefinitionContainer {
+    fn default() -> Self {
+        Self {
+            min_throw_speed: 6.0,
+            max_throw_speed: 18.0,
+            charge_time: 1.0,
+            map: Default::default(),
+        }
+    }
+}
+
+impl GrenadeDefinitionContainer {
+    const PATH: &'static str = "data/configs/grenades.ron";
+
+    pub fn new() -> Self {
+        match File::open(Self::PATH)
+            .map_err(|e| e.to_string())
+            .and_then(|file| ron::de::from_reader(file).map_err(|e| e.to_string()))
+        {
+            Ok(container) => container,
+            Err(error) => {
+                Log::err(format!(
+                    "[Grenades]: Unable to load {}! Reason: {}",
+                    Self::PATH,
+                    error
+                ));
+                Self::default()
+            }
+        }
+    }
+
+    /// Returns a definition of the grenade of the given kind, or the default definition if there
+    /// is none.
+    pub fn get(&self, kind: ItemKind) -> &GrenadeDefinition {
+        self.map.get(&kind).unwrap_or(&FALLBACK_DEFINITION)
+    }
+
+    /// Returns speed of a grenade thrown after charging for the given time.
+    pub fn throw_speed(&self, charge: f32) -> f32 {
+        let k = (charge / self.charge_time.max(f32::EPSILON)).clamp(0.0, 1.0);
+        self.min_throw_speed + (self.max_throw_speed - self.min_throw_speed) * k
+    }
+}
+
+lazy_static! {
+    pub static ref DEFINITIONS: GrenadeDefinitionContainer = GrenadeDefinitionContainer::new();
+    static ref FALLBACK_DEFINITION: GrenadeDefinition = GrenadeDefinition::default();
+}
@@ -1,15 +1,20 @@
 //! Weapon related stuff.
 
 use crate::{
+    current_level_ref,
+    inventory::Inventory,
+    level::item::ItemKind,
     utils::ResourceProxy,
     weapon::{
-        definition::{WeaponDefinition, WeaponKind},
-        projectile::Projectile,
+        definition::{FireMode, WeaponDefinition, WeaponKind},
+        projectile::{Damage, Projectile},
+        upgrade::{UpgradeStat, WeaponUpgrades},
     },
 };
 use fyrox::{
     core::{
-        algebra::{Matrix3, Vector2, Vector3},
+        algebra::{Matrix3, UnitQuaternion, Vector2, Vector3},
+        futures::executor::block_on,
         math::{vector_to_quat, Matrix4Ext},
         pool::Handle,
         reflect::prelude::*,
@@ -23,16 +28,20 @@ use fyrox::{
     scene::{
         graph::Graph,
         node::{Node, TypeUuidProvider},
+        sound::Sound,
         Scene,
     },
     script::{
         ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait,
     },
+    utils::log::Log,
 };
 
 pub mod definition;
+pub mod grenade;
 pub mod projectile;
 pub mod sight;
+pub mod upgrade;
 
 pub struct WeaponMessage {
     pub weapon: Handle<Node>,
@@ -84,10 +93,52 @@ pub struct Weapon {
     #[reflect(hidden)]
     owner: Handle<Node>,
 
+    #[reflect(hidden)]
+    #[visit(optional)]
+    upgrades: WeaponUpgrades,
+
     #[reflect(hidden)]
     #[visit(optional)]
     last_shot_time: f32,
 
+    #[reflect(hidden)]
+    #[visit(optional)]
+    loaded_ammo: u32,
+
+    #[reflect(hidden)]
+    #[visit(optional)]
+    reload_timer: f32,
+
+    #[reflect(hidden)]
+    #[visit(optional)]
+    fire_mode: u32,
+
+    // Spread (in degrees) that was accumulated by sustained fire.
+    #[reflect(hidden)]
+    #[visit(optional)]
+    shot_spread: f32,
+
+    // Spread (in degrees) caused by the state of the owner.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    stance_spread: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    aiming: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    trigger_pressed: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    burst_shots_left: u32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    charge_time: f32,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pub definition: &'static WeaponDefinition,
@@ -103,7 +154,17 @@ impl Default for Weapon {
             kind: WeaponKind::M4,
             shot_point: Handle::NONE,
             last_shot_time: 0.0,
+            loaded_ammo: 0,
+            reload_timer: 0.0,
+            fire_mode: 0,
+            shot_spread: 0.0,
+            stance_spread: 0.0,
+            aiming: false,
+            trigger_pressed: false,
+            burst_shots_left: 0,
+            charge_time: 0.0,
             owner: Handle::NONE,
+            upgrades: Default::default(),
             definition: Self::definition(WeaponKind::M4),
             flash_light: Default::default(),
             flash_light_enabled: false,
@@ -123,7 +184,73 @@ impl Default for Weapon {
 
 impl Weapon {
     pub fn definition(kind: WeaponKind) -> &'static WeaponDefinition {
-        definition::DEFINITIONS.map.get(&kind).unwrap()
+        definition::DEFINITIONS.get(kind)
+    }
+
+    /// Overwrites parameters of the prefab with the parameters from the definition of the weapon.
+    fn apply_definition(&mut self, ctx: &mut ScriptContext) {
+        let definition = self.definition;
+
+        self.shoot_interval = self.upgraded_shoot_interval().into();
+        self.ammo_consumption_per_shot = definition.ammo_consumption_per_shot.into();
+        self.v_recoil = Vector2::new(definition.v_recoil.0, definition.v_recoil.1).into();
+        self.h_recoil = Vector2::new(definition.h_recoil.0, definition.h_recoil.1).into();
+
+        if !definition.projectile.is_empty() {
+            match block_on(ctx.resource_manager.request_model(&definition.projectile)) {
+                Ok(projectile) => self.projectile = Some(projectile),
+                Err(error) => Log::err(format!(
+                    "[Weapons]: Unable to load projectile {} of {:?}! Reason: {:?}",
+                    definition.projectile, self.kind, error
+                )),
+            }
+        }
+    }
+
+    pub fn upgrades(&self) -> &WeaponUpgrades {
+        &self.upgrades
+    }
+
+    /// Replaces current upgrades of the weapon, it is used to carry upgrades between levels.
+    pub fn set_upgrades(&mut self, upgrades: WeaponUpgrades) {
+        self.upgrades = upgrades;
+        self.shoot_interval = self.upgraded_shoot_interval().into();
+    }
+
+    pub fn can_upgrade(&self, stat: UpgradeStat) -> bool {
+        self.upgrades.level(stat) < Self::definition(self.kind).upgrades.max_level
+    }
+
+    /// Raises the level of the given stat by one. Returns `false` if the stat is already at its
+    /// maximum level.
+    pub fn upgrade(&mut self, stat: UpgradeStat) -> bool {
+        if self.can_upgrade(stat) {
+            *self.upgrades.level_mut(stat) += 1;
+            self.shoot_interval = self.upgraded_shoot_interval().into();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn upgraded_shoot_interval(&self) -> f32 {
+        let definition = Self::definition(self.kind);
+        definition.shoot_interval
+            / (1.0 + self.upgrades.fire_rate as f32 * definition.upgrades.fire_rate)
+    }
+
+    /// Returns damage of a single projectile with upgrades applied.
+    pub fn damage(&self) -> Damage {
+        let definition = Self::definition(self.kind);
+        definition
+            .damage
+            .scale(1.0 + self.upgrades.damage as f32 * definition.upgrades.damage)
+    }
+
+    pub fn reload_time(&self) -> f32 {
+        let definition = Self::definition(self.kind);
+        definition.reload_time
+            / (1.0 + self.upgrades.reload_speed as f32 * definition.upgrades.reload_speed)
     }
 
     pub fn shot_position(&self, graph: &Graph) -> Vector3<f32> {
@@ -155,12 +282,204 @@ impl Weapon {
         self.owner = owner;
     }
 
-    pub fn switch_flash_light(&mut self) {
-        self.flash_light_enabled = !self.flash_light_enabled;
+    pub fn set_flash_light_enabled(&mut self, enabled: bool) {
+        self.flash_light_enabled = enabled;
     }
 
     pub fn can_shoot(&self, elapsed_time: f32) -> bool {
-        elapsed_time - self.last_shot_time >= *self.shoot_interval
+        !self.is_reloading() && elapsed_time - self.last_shot_time >= *self.shoot_interval
+    }
+
+    pub fn loaded_ammo(&self) -> u32 {
+        self.loaded_ammo
+    }
+
+    pub fn magazine_capacity(&self) -> u32 {
+        // Definition is fetched by kind, because the magazine could be loaded before `on_start`.
+        let definition = Self::definition(self.kind);
+        definition.magazine_capacity + self.upgrades.magazine * definition.upgrades.magazine
+    }
+
+    pub fn has_ammo_for_shot(&self) -> bool {
+        self.loaded_ammo >= *self.ammo_consumption_per_shot
+    }
+
+    /// Takes ammo for a single shot from the magazine. Returns `false` if the magazine does not
+    /// have enough ammo, in this case it should be reloaded.
+    pub fn try_consume_ammo(&mut self) -> bool {
+        if self.has_ammo_for_shot() {
+            self.loaded_ammo -= *self.ammo_consumption_per_shot;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.reload_timer > 0.0
+    }
+
+    pub fn can_reload(&self, inventory: &Inventory) -> bool {
+        !self.is_reloading()
+            && self.loaded_ammo < self.magazine_capacity()
+            && inventory.item_count(ItemKind::Ammo) > 0
+    }
+
+    pub fn begin_reload(&mut self, inventory: &Inventory) -> bool {
+        if self.can_reload(inventory) {
+            self.reload_timer = self.reload_time();
+            self.burst_shots_left = 0;
+            self.charge_time = 0.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn cancel_reload(&mut self) {
+        self.reload_timer = 0.0;
+    }
+
+    /// Advances current reload (if any) and fills the magazine from the inventory when it is done.
+    pub fn update_reload(&mut self, inventory: &mut Inventory, dt: f32) {
+        if self.is_reloading() {
+            self.reload_timer -= dt;
+
+            if !self.is_reloading() {
+                self.load_from(inventory);
+            }
+        }
+    }
+
+    /// Ends current reload (if any) immediately and fills the magazine from the inventory.
+    pub fn finish_reload(&mut self, inventory: &mut Inventory) {
+        if self.is_reloading() {
+            self.reload_timer = 0.0;
+            self.load_from(inventory);
+        }
+    }
+
+    /// Moves as much ammo as possible from the inventory to the magazine. The magazine could be
+    /// filled partially if there's not enough ammo in the inventory.
+    pub fn load_from(&mut self, inventory: &mut Inventory) {
+        let required = self.magazine_capacity().saturating_sub(self.loaded_ammo);
+        let amount = required.min(inventory.item_count(ItemKind::Ammo));
+        if amount > 0 {
+            self.loaded_ammo += inventory.try_extract_exact_items(ItemKind::Ammo, amount);
+        }
+    }
+
+    /// Empties the magazine, returns amount of ammo that was in it.
+    pub fn unload(&mut self) -> u32 {
+        self.cancel_reload();
+        std::mem::take(&mut self.loaded_ammo)
+    }
+
+    pub fn fire_mode(&self) -> FireMode {
+        Self::definition(self.kind)
+            .fire_modes
+            .get(self.fire_mode as usize)
+            .cloned()
+            .unwrap_or(FireMode::Semi)
+    }
+
+    /// Switches the weapon to the next available fire mode.
+    pub fn cycle_fire_mode(&mut self) {
+        let count = Self::definition(self.kind).fire_modes.len().max(1) as u32;
+        self.fire_mode = (self.fire_mode + 1) % count;
+        self.burst_shots_left = 0;
+        self.charge_time = 0.0;
+    }
+
+    pub fn is_trigger_pressed(&self) -> bool {
+        self.trigger_pressed
+    }
+
+    /// Returns a state of the trigger that makes the weapon fire as often as its fire mode
+    /// allows. It is used by bots, that do not have to release the trigger on their own.
+    pub fn auto_trigger_state(&self) -> bool {
+        match self.fire_mode() {
+            FireMode::Auto => true,
+            FireMode::Charged(time) => self.charge_time < time,
+            FireMode::Semi | FireMode::Burst(_) => !self.trigger_pressed,
+        }
+    }
+
+    /// Handles the trigger according to the current fire mode. Returns `true` if the weapon must
+    /// shoot right now, it is up to the caller to take the ammo and to send the shot message.
+    pub fn update_trigger(&mut self, pressed: bool, elapsed_time: f32, dt: f32) -> bool {
+        let just_pressed = pressed && !self.trigger_pressed;
+        let just_released = !pressed && self.trigger_pressed;
+        self.trigger_pressed = pressed;
+
+        let can_shoot = self.can_shoot(elapsed_time);
+
+        match self.fire_mode() {
+            FireMode::Semi => just_pressed && can_shoot,
+            FireMode::Auto => pressed && can_shoot,
+            FireMode::Burst(count) => {
+                if just_pressed && self.burst_shots_left == 0 {
+                    self.burst_shots_left = count;
+                }
+
+                if self.burst_shots_left > 0 && can_shoot {
+                    self.burst_shots_left -= 1;
+                    true
+                } else {
+                    false
+                }
+            }
+            FireMode::Charged(time) => {
+                if pressed {
+                    self.charge_time += dt;
+                    false
+                } else if just_released {
+                    let charged = self.charge_time >= time;
+                    self.charge_time = 0.0;
+                    charged && can_shoot
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Returns charge progress in `[0; 1]` range for charged weapons.
+    pub fn charge(&self) -> f32 {
+        if let FireMode::Charged(time) = self.fire_mode() {
+            (self.charge_time / time.max(f32::EPSILON)).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Updates the accuracy of the weapon according to the state of its owner. Must be called
+    /// every frame by the owner.
+    pub fn update_spread(&mut self, moving: bool, running: bool, aiming: bool, dt: f32) {
+        let spread = &Self::definition(self.kind).spread;
+
+        self.shot_spread = (self.shot_spread - spread.recovery * dt).max(0.0);
+
+        self.stance_spread = if running {
+            spread.running
+        } else if moving {
+            spread.moving
+        } else {
+            0.0
+        };
+
+        self.aiming = aiming;
+    }
+
+    /// Returns the angle (in degrees) of the cone in which projectiles are fired.
+    pub fn spread(&self) -> f32 {
+        let spread = &Self::definition(self.kind).spread;
+        let factor = if self.aiming {
+            spread.aiming_factor
+        } else {
+            1.0
+        };
+        ((spread.base + self.shot_spread + self.stance_spread) * factor).min(spread.max)
     }
 
     pub fn gen_v_recoil_angle(&self) -> f32 {
@@ -183,21 +502,40 @@ impl Weapon {
         self.last_shot_time = elapsed_time;
 
         let shot_position = self.shot_position(&scene.graph);
-        let direction = direction
-            .unwrap_or_else(|| self.shot_direction(&scene.graph))
-            .try_normalize(f32::EPSILON)
-            .unwrap_or_else(Vector3::z);
+        let direction = deviate(
+            direction
+                .unwrap_or_else(|| self.shot_direction(&scene.graph))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::z),
+            self.spread(),
+        );
+
+        let spread = &self.definition.spread;
+        self.shot_spread = (self.shot_spread + spread.per_shot).min(spread.max);
 
         if let Some(vfx) = self
             .shot_vfx
             .choose(&mut fyrox::rand::thread_rng())
             .and_then(|vfx| vfx.0.as_ref())
         {
-            vfx.instantiate_at(scene, shot_position, vector_to_quat(direction));
+            let vfx = vfx.instantiate_at(scene, shot_position, vector_to_quat(direction));
+
+            // Shot sounds are defined in weapons.ron, sounds of the effect prefab would play along
+            // with them.
+            if !self.definition.shot_sounds.is_empty() {
+                let sounds = scene
+                    .graph
+                    .traverse_handle_iter(vfx)
+                    .filter(|h| scene.graph[*h].cast::<Sound>().is_some())
+                    .collect::<Vec<_>>();
+                for sound in sounds {
+                    scene.graph.remove_node(sound);
+                }
+            }
         }
 
         if let Some(model) = self.projectile.as_ref() {
-            Projectile::spawn(
+            let projectile = Projectile::spawn(
                 model,
                 scene,
                 direction,
@@ -205,6 +543,10 @@ impl Weapon {
                 self_handle,
                 Default::default(),
             );
+            if let Some(projectile) = scene.graph[projectile].try_get_script_mut::<Projectile>() {
+                projectile.set_damage(self.damage());
+                projectile.set_penetration(self.definition.penetration, self.definition.ricochets);
+            }
         }
     }
 }
@@ -222,6 +564,8 @@ impl ScriptTrait for Weapon {
         self.definition = Self::definition(self.kind);
         self.self_handle = ctx.handle;
 
+        self.apply_definition(ctx);
+
         ctx.message_dispatcher
             .subscribe_to::<WeaponMessage>(ctx.handle);
     }
@@ -251,6 +595,24 @@ impl ScriptTrait for Weapon {
 
             if let WeaponMessageData::Shoot { direction } = msg.data {
                 self.shoot(ctx.handle, ctx.scene, ctx.elapsed_time, direction);
+
+                if let Some(shot_sound) = self
+                    .definition
+                    .shot_sounds
+                    .choose(&mut fyrox::rand::thread_rng())
+                {
+                    if let Some(level) = current_level_ref(ctx.plugins) {
+                        let position = self.shot_position(&ctx.scene.graph);
+                        level.sound_manager.play_sound(
+                            &mut ctx.scene.graph,
+                            shot_sound,
+                            position,
+                            1.0,
+                            1.0,
+                            3.0,
+                        );
+                    }
+                }
             }
         }
     }
@@ -260,6 +622,29 @@ impl ScriptTrait for Weapon {
     }
 }
 
+/// Randomly rotates the direction within a cone with the given angle (in degrees).
+fn deviate(direction: Vector3<f32>, angle: f32) -> Vector3<f32> {
+    let half_angle = angle.to_radians() * 0.5;
+    if half_angle <= 0.0 {
+        return direction;
+    }
+
+    let mut rng = fyrox::rand::thread_rng();
+
+    let side = direction
+        .cross(&Vector3::y())
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(Vector3::x);
+
+    // Tilt the direction away by a random angle and then spin it around the original direction.
+    let tilt = UnitQuaternion::from_scaled_axis(side.scale(rng.gen_range(0.0..half_angle)));
+    let spin = UnitQuaternion::from_scaled_axis(
+        direction.scale(rng.gen_range(0.0..std::f32::consts::TAU)),
+    );
+
+    spin * (tilt * direction)
+}
+
 pub fn try_weapon_mut(handle: Handle<Node>, graph: &mut Graph) -> Option<&mut Weapon> {
     graph
         .try_get_mut(handle)
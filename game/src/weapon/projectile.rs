@@ -4,9 +4,16 @@ use crate::{
         character_ref, try_get_character_ref, Character, CharacterMessage, CharacterMessageData,
         DamageDealer, HitBox,
     },
-    current_level_ref, game_ref,
-    level::decal::Decal,
+    current_level_ref,
+    effects::rail::Rail,
+    level::{
+        decal::Decal,
+        destructible::{is_part_of, DestructibleMessage},
+    },
+    sound::MaterialType,
+    status_effect::StatusEffectKind,
     utils::ResourceProxy,
+    weapon::grenade::EmpMessage,
     CollisionGroups, Weapon,
 };
 use fyrox::{
@@ -21,6 +28,7 @@ use fyrox::{
         visitor::prelude::*,
     },
     impl_component_provider,
+    lazy_static::lazy_static,
     rand::seq::SliceRandom,
     resource::model::Model,
     scene::{
@@ -30,27 +38,66 @@ use fyrox::{
             Graph,
         },
         node::{Node, TypeUuidProvider},
-        rigidbody::RigidBody,
+        rigidbody::{RigidBody, RigidBodyType},
         sound::SoundBufferResource,
         Scene,
     },
-    script::{ScriptContext, ScriptTrait},
+    script::{ScriptContext, ScriptMessageSender, ScriptTrait},
+    utils::log::Log,
 };
 use serde::Deserialize;
-use std::hash::{Hash, Hasher};
+use std::{
+    collections::HashMap,
+    fs::File,
+    hash::{Hash, Hasher},
+};
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
+#[derive(
+    Deserialize,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum DamageType {
+    Ballistic,
+    Energy,
+    Explosive,
+    Fire,
+    Melee,
+    Crush,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        Self::Ballistic
+    }
+}
+
 #[derive(
     Deserialize, Copy, Clone, Debug, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames,
 )]
 pub enum Damage {
-    Splash { radius: f32, amount: f32 },
-    Point(f32),
+    Splash {
+        radius: f32,
+        amount: f32,
+        #[visit(optional)]
+        kind: DamageType,
+    },
+    Point(f32, #[visit(optional)] DamageType),
 }
 
 impl Default for Damage {
     fn default() -> Self {
-        Self::Point(0.0)
+        Self::Point(0.0, Default::default())
     }
 }
 
@@ -58,20 +105,341 @@ impl Damage {
     #[must_use]
     pub fn scale(&self, k: f32) -> Self {
         match *self {
-            Self::Splash { amount, radius } => Self::Splash {
+            Self::Splash {
+                amount,
+                radius,
+                kind,
+            } => Self::Splash {
                 amount: amount * k.abs(),
                 radius,
+                kind,
             },
-            Self::Point(amount) => Self::Point(amount * k.abs()),
+            Self::Point(amount, kind) => Self::Point(amount * k.abs(), kind),
         }
     }
 
     pub fn amount(&self) -> f32 {
         *match self {
             Damage::Splash { amount, .. } => amount,
-            Damage::Point(amount) => amount,
+            Damage::Point(amount, _) => amount,
+        }
+    }
+
+    pub fn kind(&self) -> DamageType {
+        match *self {
+            Damage::Splash { kind, .. } => kind,
+            Damage::Point(_, kind) => kind,
+        }
+    }
+
+    #[must_use]
+    pub fn with_kind(self, kind: DamageType) -> Self {
+        match self {
+            Self::Splash { radius, amount, .. } => Self::Splash {
+                radius,
+                amount,
+                kind,
+            },
+            Self::Point(amount, _) => Self::Point(amount, kind),
+        }
+    }
+}
+
+/// Parameters of projectiles passing through obstacles and ricocheting off them.
+#[derive(Deserialize)]
+pub struct PenetrationDefinition {
+    /// Fraction of the damage a projectile loses when it passes through an obstacle of a material
+    /// that is not listed in `material_loss`.
+    pub default_loss: f32,
+    pub material_loss: HashMap<MaterialType, f32>,
+    /// Damage of a projectile is multiplied by this value on every ricochet.
+    pub ricochet_damage_factor: f32,
+}
+
+impl Default for PenetrationDefinition {
+    fn default() -> Self {
+        Self {
+            default_loss: 0.5,
+            material_loss: Default::default(),
+            ricochet_damage_factor: 0.6,
+        }
+    }
+}
+
+impl PenetrationDefinition {
+    const PATH: &'static str = "data/configs/penetration.ron";
+
+    pub fn new() -> Self {
+        match File::open(Self::PATH)
+            .map_err(|e| e.to_string())
+            .and_then(|file| ron::de::from_reader(file).map_err(|e| e.to_string()))
+        {
+            Ok(definition) => definition,
+            Err(error) => {
+                Log::err(format!(
+                    "[Penetration]: Unable to load {}! Reason: {}",
+                    Self::PATH,
+                    error
+                ));
+                Self::default()
+            }
         }
     }
+
+    pub fn loss(&self, material: Option<MaterialType>) -> f32 {
+        material
+            .and_then(|material| self.material_loss.get(&material).cloned())
+            .unwrap_or(self.default_loss)
+    }
+}
+
+lazy_static! {
+    static ref PENETRATION: PenetrationDefinition = PenetrationDefinition::new();
+}
+
+/// An explosion that damages everything around its center. Damage falls off linearly with the
+/// distance, any obstacle between the center and a target absorbs it completely.
+pub struct Splash {
+    pub center: Vector3<f32>,
+    pub radius: f32,
+    pub amount: f32,
+    pub kind: DamageType,
+    /// An entity that caused the explosion.
+    pub dealer: Handle<Node>,
+    pub critical_hit_probability: f32,
+    /// An effect that is applied to every character hit by the explosion.
+    pub status_effect: Option<StatusEffectKind>,
+    /// Time (in seconds) for which turrets and doors hit by the explosion are disabled.
+    pub emp_duration: f32,
+}
+
+impl Splash {
+    /// Velocity change that a unit of damage gives to a body of unit mass.
+    const IMPULSE_PER_DAMAGE: f32 = 0.05;
+
+    fn falloff(&self, point: Vector3<f32>) -> Option<f32> {
+        let distance = point.metric_distance(&self.center);
+        if distance <= self.radius {
+            Some(1.0 - distance / self.radius)
+        } else {
+            None
+        }
+    }
+
+    fn direction_to(&self, point: Vector3<f32>) -> Vector3<f32> {
+        (point - self.center)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y)
+    }
+
+    /// Damages actors and destructible entities, disables doors (if it is an EMP) and pushes
+    /// dynamic rigid bodies in the radius. Nodes of the `ignored` sub-graph (usually the projectile itself) are
+    /// not affected and do not occlude.
+    pub fn apply(
+        &self,
+        scene: &mut Scene,
+        actors: &[Handle<Node>],
+        destructibles: &[Handle<Node>],
+        doors: &[Handle<Node>],
+        ignored: Handle<Node>,
+        script_message_sender: &ScriptMessageSender,
+    ) {
+        for &actor_handle in actors {
+            let character = character_ref(actor_handle, &scene.graph);
+
+            // Damage goes to the closest visible hit box, so head and limb multipliers apply.
+            let mut targets = if character.hit_boxes.is_empty() {
+                vec![(None, character.position(&scene.graph))]
+            } else {
+                character
+                    .hit_boxes
+                    .iter()
+                    .map(|h| (Some(*h), scene.graph[h.collider].global_position()))
+                    .collect::<Vec<_>>()
+            };
+            targets.sort_by(|(_, a), (_, b)| {
+                a.metric_distance(&self.center)
+                    .total_cmp(&b.metric_distance(&self.center))
+            });
+            let hit_box_colliders = character
+                .hit_boxes
+                .iter()
+                .map(|h| h.collider)
+                .collect::<Vec<_>>();
+
+            for (hitbox, point) in targets {
+                let falloff = match self.falloff(point) {
+                    Some(falloff) => falloff,
+                    None => break,
+                };
+
+                if is_occluded(&mut scene.graph, self.center, point, |graph, collider| {
+                    hit_box_colliders.contains(&collider) || is_part_of(graph, collider, ignored)
+                }) {
+                    continue;
+                }
+
+                // Non-lethal grenades have no damage at all.
+                if self.amount > 0.0 {
+                    script_message_sender.send_global(CharacterMessage {
+                        character: actor_handle,
+                        data: CharacterMessageData::Damage {
+                            dealer: DamageDealer {
+                                entity: self.dealer,
+                            },
+                            hitbox,
+                            amount: self.amount * falloff * hitbox.map_or(1.0, |h| h.damage_factor),
+                            kind: self.kind,
+                            critical_hit_probability: self.critical_hit_probability,
+                            position: Some(DamagePosition {
+                                point,
+                                direction: self.direction_to(point),
+                            }),
+                        },
+                    });
+                }
+
+                if let Some(status_effect) = self.status_effect {
+                    script_message_sender.send_global(CharacterMessage {
+                        character: actor_handle,
+                        data: CharacterMessageData::ApplyStatusEffect(status_effect),
+                    });
+                }
+
+                break;
+            }
+        }
+
+        for &destructible in destructibles {
+            let point = scene.graph[destructible].global_position();
+            if let Some(falloff) = self.falloff(point) {
+                if !is_occluded(&mut scene.graph, self.center, point, |graph, collider| {
+                    is_part_of(graph, collider, destructible)
+                        || is_part_of(graph, collider, ignored)
+                }) {
+                    if self.amount > 0.0 {
+                        script_message_sender.send_to_target(
+                            destructible,
+                            DestructibleMessage {
+                                destructible,
+                                amount: self.amount * falloff,
+                            },
+                        );
+                    }
+
+                    // Turrets are destructible, every other destructible just ignores EMP.
+                    if self.emp_duration > 0.0 {
+                        script_message_sender.send_to_target(
+                            destructible,
+                            EmpMessage {
+                                target: destructible,
+                                duration: self.emp_duration,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        if self.emp_duration > 0.0 {
+            for &door in doors {
+                let point = scene.graph[door].global_position();
+                if self.falloff(point).is_some()
+                    && !is_occluded(&mut scene.graph, self.center, point, |graph, collider| {
+                        is_part_of(graph, collider, door) || is_part_of(graph, collider, ignored)
+                    })
+                {
+                    script_message_sender.send_to_target(
+                        door,
+                        EmpMessage {
+                            target: door,
+                            duration: self.emp_duration,
+                        },
+                    );
+                }
+            }
+        }
+
+        // Only colliders within the radius are queried. Capsule bodies of actors are moved by
+        // actors themselves, so they are not pushed, but ragdolls are.
+        let mut colliders = Vec::<Handle<Node>>::new();
+        scene.graph.physics.intersections_with_shape(
+            ColliderShape::ball(self.radius),
+            Point3::from(self.center),
+            InteractionGroups::new(
+                BitMask(0xFFFF),
+                BitMask(!(CollisionGroups::ActorCapsule as u32)),
+            ),
+            &mut colliders,
+        );
+
+        let mut bodies = Vec::<(Handle<Node>, Vector3<f32>)>::new();
+        for collider in colliders {
+            let body_handle = scene.graph[collider].parent();
+            if bodies.iter().any(|(b, _)| *b == body_handle)
+                || is_part_of(&scene.graph, body_handle, ignored)
+                || actors.iter().any(|a| {
+                    try_get_character_ref(*a, &scene.graph).map_or(false, |c| c.body == body_handle)
+                })
+            {
+                continue;
+            }
+
+            if let Some(body) = scene.graph.try_get_of_type::<RigidBody>(body_handle) {
+                if body.body_type() == RigidBodyType::Dynamic {
+                    bodies.push((body_handle, body.global_position()));
+                }
+            }
+        }
+
+        for (body_handle, point) in bodies {
+            if let Some(falloff) = self.falloff(point) {
+                if is_occluded(&mut scene.graph, self.center, point, |graph, collider| {
+                    is_part_of(graph, collider, body_handle) || is_part_of(graph, collider, ignored)
+                }) {
+                    continue;
+                }
+
+                let direction = self.direction_to(point);
+                if let Some(body) = scene.graph[body_handle].cast_mut::<RigidBody>() {
+                    let velocity_change = direction.scale(
+                        self.amount * falloff * Self::IMPULSE_PER_DAMAGE
+                            / body.mass().max(f32::EPSILON),
+                    );
+                    body.set_lin_vel(body.lin_vel() + velocity_change);
+                }
+            }
+        }
+    }
+}
+
+/// Checks whether there's an obstacle between two points. Intersections with the colliders for
+/// which `ignore` returns `true` are skipped, as well as intersections right at the beginning.
+fn is_occluded<F>(graph: &mut Graph, begin: Vector3<f32>, end: Vector3<f32>, mut ignore: F) -> bool
+where
+    F: FnMut(&Graph, Handle<Node>) -> bool,
+{
+    let ray_direction = end - begin;
+
+    let mut query_buffer = Vec::<Intersection>::new();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(begin),
+            ray_direction,
+            max_len: ray_direction.norm(),
+            groups: InteractionGroups::new(
+                BitMask(0xFFFF),
+                BitMask(!(CollisionGroups::ActorCapsule as u32)),
+            ),
+            sort_results: false,
+        },
+        &mut query_buffer,
+    );
+
+    // The center of an explosion usually lies right on a surface, so ignore the surface itself.
+    query_buffer
+        .iter()
+        .any(|i| i.toi > 0.05 && !ignore(graph, i.collider))
 }
 
 #[derive(Clone, Debug)]
@@ -156,6 +524,53 @@ pub struct Projectile {
     #[reflect(min_value = 0.0, max_value = 1.0)]
     critical_hit_probability: f32,
 
+    // Additional effects of splash damage, they're set by the thrower (see grenade definitions).
+    #[visit(optional)]
+    #[reflect(hidden)]
+    status_effect: Option<StatusEffectKind>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    emp_duration: f32,
+
+    #[visit(optional)]
+    #[reflect(description = "Maximum amount of obstacles the projectile could pass through.")]
+    penetration: u32,
+
+    #[visit(optional)]
+    #[reflect(
+        description = "Maximum thickness (in meters) of an obstacle the projectile could pass through."
+    )]
+    penetration_depth: f32,
+
+    #[visit(optional)]
+    #[reflect(description = "Maximum amount of ricochets off metal surfaces.")]
+    max_ricochets: u32,
+
+    #[visit(optional)]
+    #[reflect(
+        description = "Maximum angle (in degrees) between a metal surface and the direction of the projectile at which it ricochets."
+    )]
+    ricochet_angle: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    penetrated: u32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ricochets: u32,
+
+    // A prefab of the appear effect with `Rail` script, every segment of the path of the
+    // projectile gets its own instance of it.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rail_effect: Option<Model>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rail: Handle<Node>,
+
     // A handle to collider of the projectile. It is used as a cache to prevent searching for it
     // every frame.
     #[visit(skip)]
@@ -187,12 +602,50 @@ impl Default for Projectile {
             one_frame: false,
             damage: Default::default(),
             critical_hit_probability: 0.025,
+            status_effect: None,
+            emp_duration: 0.0,
+            penetration: 0,
+            penetration_depth: 0.5,
+            max_ricochets: 0,
+            ricochet_angle: 15.0,
+            penetrated: 0,
+            ricochets: 0,
+            rail_effect: None,
+            rail: Default::default(),
             collider: Default::default(),
         }
     }
 }
 
 impl Projectile {
+    pub fn set_damage(&mut self, damage: Damage) {
+        self.damage = damage;
+    }
+
+    pub fn set_damage_type(&mut self, kind: DamageType) {
+        self.damage = self.damage.with_kind(kind);
+    }
+
+    pub fn scale_damage(&mut self, k: f32) {
+        self.damage = self.damage.scale(k);
+    }
+
+    /// Sets additional effects of splash damage.
+    pub fn set_splash_effects(
+        &mut self,
+        status_effect: Option<StatusEffectKind>,
+        emp_duration: f32,
+    ) {
+        self.status_effect = status_effect;
+        self.emp_duration = emp_duration;
+    }
+
+    /// Sets amount of obstacles the projectile could pass through and amount of ricochets.
+    pub fn set_penetration(&mut self, penetration: u32, max_ricochets: u32) {
+        self.penetration = penetration;
+        self.max_ricochets = max_ricochets;
+    }
+
     pub fn spawn(
         resource: &Model,
         scene: &mut Scene,
@@ -222,6 +675,290 @@ impl Projectile {
 
         instance_handle
     }
+
+    /// Damages whatever was hit and spawns impact effects.
+    fn apply_hit(&self, hit: &Hit, direction: Vector3<f32>, ctx: &mut ScriptContext) {
+        match self.damage {
+            Damage::Splash {
+                radius,
+                amount,
+                kind,
+            } => {
+                let level = current_level_ref(ctx.plugins).unwrap();
+                Splash {
+                    center: hit.position,
+                    radius,
+                    amount,
+                    kind,
+                    dealer: hit.shooter_actor,
+                    critical_hit_probability: self.critical_hit_probability,
+                    status_effect: self.status_effect,
+                    emp_duration: self.emp_duration,
+                }
+                .apply(
+                    ctx.scene,
+                    &level.actors,
+                    &level.destructibles,
+                    &level.doors_container.doors,
+                    ctx.handle,
+                    ctx.message_sender,
+                );
+            }
+            Damage::Point(amount, kind) => {
+                let amount = amount * hit.hit_box.map_or(1.0, |h| h.damage_factor);
+
+                ctx.message_sender.send_global(CharacterMessage {
+                    character: hit.hit_actor,
+                    data: CharacterMessageData::Damage {
+                        dealer: DamageDealer {
+                            entity: hit.shooter_actor,
+                        },
+                        hitbox: hit.hit_box,
+                        amount,
+                        kind,
+                        critical_hit_probability: self.critical_hit_probability,
+                        position: Some(DamagePosition {
+                            point: hit.position,
+                            direction,
+                        }),
+                    },
+                });
+
+                if hit.hit_actor.is_none() {
+                    let level = current_level_ref(ctx.plugins).unwrap();
+                    if let Some(&destructible) = level
+                        .destructibles
+                        .iter()
+                        .find(|&&d| is_part_of(&ctx.scene.graph, hit.collider, d))
+                    {
+                        ctx.message_sender.send_to_target(
+                            destructible,
+                            DestructibleMessage {
+                                destructible,
+                                amount,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Ok(effect_prefab) = block_on(ctx.resource_manager.request_model(
+            if hit.hit_actor.is_some() {
+                "data/models/blood_splatter.rgs"
+            } else {
+                "data/models/bullet_impact.rgs"
+            },
+        )) {
+            effect_prefab.instantiate_at(ctx.scene, hit.position, vector_to_quat(hit.normal));
+        }
+
+        if let Some(impact_sound) = self.impact_sound.as_ref() {
+            current_level_ref(ctx.plugins)
+                .unwrap()
+                .sound_manager
+                .play_sound_buffer(
+                    &mut ctx.scene.graph,
+                    impact_sound,
+                    hit.position,
+                    1.0,
+                    4.0,
+                    3.0,
+                );
+        }
+
+        Decal::new_bullet_hole(
+            ctx.resource_manager,
+            &mut ctx.scene.graph,
+            hit.position,
+            hit.normal,
+            hit.collider,
+            if hit.hit_actor.is_some() {
+                Color::opaque(160, 0, 0)
+            } else {
+                Color::opaque(20, 20, 20)
+            },
+        );
+
+        // Add blood splatter on a surface behind an actor that was shot.
+        if try_get_character_ref(hit.hit_actor, &ctx.scene.graph).is_some() {
+            for intersection in hit.query_buffer.iter() {
+                if matches!(
+                    ctx.scene.graph[intersection.collider].as_collider().shape(),
+                    ColliderShape::Trimesh(_)
+                ) && intersection.position.coords.metric_distance(&hit.position) < 2.0
+                {
+                    Decal::spawn(
+                        &mut ctx.scene.graph,
+                        intersection.position.coords,
+                        hit.normal,
+                        Handle::NONE,
+                        Color::opaque(255, 255, 255),
+                        Vector3::new(0.45, 0.45, 0.2),
+                        ctx.resource_manager
+                            .request_texture("data/textures/decals/BloodSplatter_BaseColor.png"),
+                    );
+
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Decides whether the projectile ricochets off or passes through the obstacle it has hit.
+    /// Returns the next segment of the path of the projectile, or `None` if it stops.
+    fn pass_through(
+        &mut self,
+        hit: &Hit,
+        begin: Vector3<f32>,
+        end: Vector3<f32>,
+        ctx: &mut ScriptContext,
+        ignored_colliders: &mut Vec<Handle<Node>>,
+    ) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        let dir = (end - begin).try_normalize(f32::EPSILON)?;
+        let remaining = hit.position.metric_distance(&end);
+
+        let material = if hit.hit_actor.is_some() {
+            Some(MaterialType::Flesh)
+        } else {
+            current_level_ref(ctx.plugins)?
+                .sound_manager
+                .material_of(hit.collider, hit.feature)
+        };
+
+        if self.ricochets < self.max_ricochets && material == Some(MaterialType::Metal) {
+            let normal = hit.normal.try_normalize(f32::EPSILON)?;
+            let grazing_angle = dir.dot(&normal).abs().clamp(0.0, 1.0).asin();
+            if grazing_angle < self.ricochet_angle.to_radians() {
+                self.ricochets += 1;
+                self.damage = self.damage.scale(PENETRATION.ricochet_damage_factor);
+
+                let reflected = dir - normal.scale(2.0 * dir.dot(&normal));
+                let begin = hit.position - dir.scale(0.01);
+
+                ignored_colliders.clear();
+                ignored_colliders.push(self.collider);
+
+                return Some((begin, begin + reflected.scale(remaining)));
+            }
+        }
+
+        if self.penetrated < self.penetration {
+            let loss = PENETRATION.loss(material);
+            if loss < 1.0 {
+                let exit = if hit.hit_actor.is_some() {
+                    // Characters are thin enough, so just skip the rest of the character.
+                    let character = character_ref(hit.hit_actor, &ctx.scene.graph);
+                    ignored_colliders.extend(character.hit_boxes.iter().map(|h| h.collider));
+                    ignored_colliders.push(character.capsule_collider);
+                    Some(hit.position)
+                } else {
+                    find_exit(&mut ctx.scene.graph, hit, dir, self.penetration_depth)
+                };
+
+                if let Some(exit) = exit {
+                    self.penetrated += 1;
+                    self.damage = self.damage.scale(1.0 - loss);
+
+                    let begin = exit + dir.scale(0.01);
+                    let remaining = (remaining - hit.position.metric_distance(&begin)).max(0.0);
+
+                    return Some((begin, begin + dir.scale(remaining)));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Makes rail effects follow the path of the projectile. The first segment uses the rail that
+    /// was spawned with the projectile, every other segment gets a new one. Segments that end
+    /// with a hit have explicit length, the rest are ray cast by the rail itself.
+    fn update_rails(&mut self, segments: &[(Vector3<f32>, Vector3<f32>, bool)], scene: &mut Scene) {
+        let rail_effect = match self.rail_effect.as_ref() {
+            Some(rail_effect) => rail_effect,
+            None => return,
+        };
+
+        // The first rail belongs to the segment that starts at the spawn point only.
+        let first_rail = std::mem::take(&mut self.rail);
+
+        for (i, &(begin, end, closed)) in segments.iter().enumerate() {
+            let rail = if i == 0 {
+                first_rail
+            } else {
+                let dir = (end - begin)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::z);
+                let instance = rail_effect.instantiate(scene);
+                scene.graph[instance]
+                    .local_transform_mut()
+                    .set_position(begin)
+                    .set_rotation(vector_to_quat(dir));
+                find_rail(&scene.graph, instance)
+            };
+
+            if closed {
+                if let Some(rail) = scene
+                    .graph
+                    .try_get_mut(rail)
+                    .and_then(|n| n.try_get_script_mut::<Rail>())
+                {
+                    rail.set_length(begin.metric_distance(&end));
+                }
+            }
+        }
+    }
+
+    /// Instantiates an appear effect and remembers it if it is a rail.
+    fn spawn_appear_effect(&mut self, effect: &Model, scene: &mut Scene, position: Vector3<f32>) {
+        let instance = effect.instantiate(scene);
+        scene.graph[instance]
+            .local_transform_mut()
+            .set_position(position)
+            .set_rotation(vector_to_quat(self.dir));
+
+        let rail = find_rail(&scene.graph, instance);
+        if rail.is_some() {
+            self.rail = rail;
+            self.rail_effect = Some(effect.clone());
+        }
+    }
+}
+
+/// Finds a point at which a ray that has entered an obstacle at the given hit leaves it. Returns
+/// `None` if the obstacle is thicker than `max_depth`.
+fn find_exit(
+    graph: &mut Graph,
+    hit: &Hit,
+    dir: Vector3<f32>,
+    max_depth: f32,
+) -> Option<Vector3<f32>> {
+    // Cast a ray backwards from the deepest point, the first intersection with the obstacle is
+    // the exit point. If there's none (except the entry point), the obstacle is too thick.
+    let mut query_buffer = Vec::default();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(hit.position + dir.scale(max_depth)),
+            ray_direction: -dir,
+            max_len: max_depth,
+            groups: InteractionGroups::default(),
+            sort_results: true,
+        },
+        &mut query_buffer,
+    );
+
+    query_buffer
+        .iter()
+        .find(|i| i.collider == hit.collider && i.toi > 0.0 && i.toi < max_depth - 0.01)
+        .map(|i| i.position.coords)
+}
+
+fn find_rail(graph: &Graph, root: Handle<Node>) -> Handle<Node> {
+    graph
+        .find(root, &mut |n| n.has_script::<Rail>())
+        .map(|(h, _)| h)
+        .unwrap_or_default()
 }
 
 fn ray_hit(
@@ -230,7 +967,7 @@ fn ray_hit(
     shooter: Handle<Node>,
     actors: &[Handle<Node>],
     graph: &mut Graph,
-    ignored_collider: Handle<Node>,
+    ignored_colliders: &[Handle<Node>],
 ) -> Option<Hit> {
     if begin == end {
         return None;
@@ -257,7 +994,10 @@ fn ray_hit(
     );
 
     // List of hits sorted by distance from ray origin.
-    if let Some(hit) = query_buffer.iter().find(|i| i.collider != ignored_collider) {
+    if let Some(hit) = query_buffer
+        .iter()
+        .find(|i| !ignored_colliders.contains(&i.collider))
+    {
         // Check if there was an intersection with an actor.
         'actor_loop: for &actor_handle in actors.iter() {
             let character = character_ref(actor_handle, graph);
@@ -325,16 +1065,16 @@ impl ScriptTrait for Projectile {
             rigid_body.set_lin_vel(self.initial_velocity);
         }
 
-        if let Some(appear_effect) = self.appear_effect.as_ref() {
-            appear_effect.instantiate_at(ctx.scene, current_position, vector_to_quat(self.dir));
+        if let Some(appear_effect) = self.appear_effect.clone() {
+            self.spawn_appear_effect(&appear_effect, ctx.scene, current_position);
         }
 
         if let Some(vfx) = self
             .random_appear_effects
             .choose(&mut fyrox::rand::thread_rng())
-            .and_then(|vfx| vfx.0.as_ref())
+            .and_then(|vfx| vfx.0.clone())
         {
-            vfx.instantiate_at(ctx.scene, current_position, vector_to_quat(self.dir));
+            self.spawn_appear_effect(&vfx, ctx.scene, current_position);
         }
     }
 
@@ -350,9 +1090,6 @@ impl ScriptTrait for Projectile {
     }
 
     fn on_update(&mut self, ctx: &mut ScriptContext) {
-        let game = game_ref(ctx.plugins);
-        let level = current_level_ref(ctx.plugins).unwrap();
-
         // Movement of kinematic projectiles is controlled explicitly.
         if let Some(speed) = self.speed {
             let total_velocity = self.dir.scale(speed);
@@ -374,21 +1111,68 @@ impl ScriptTrait for Projectile {
         let direction = position - self.last_position;
 
         let mut hit = None;
+        let mut stopped = false;
+        let mut path_hit = false;
 
         if self.use_ray_casting {
-            hit = ray_hit(
-                self.last_position,
-                position,
+            // The path of the projectile may consist of multiple segments if it passes through
+            // obstacles or ricochets.
+            let mut begin = self.last_position;
+            let mut end = position;
+            // Ignore self collider.
+            let mut ignored_colliders = vec![self.collider];
+            let mut segments = Vec::new();
+
+            while let Some(segment_hit) = ray_hit(
+                begin,
+                end,
                 self.owner,
                 &current_level_ref(ctx.plugins).unwrap().actors,
                 &mut ctx.scene.graph,
-                // Ignore self collider.
-                self.collider,
-            );
-            self.last_position = position;
+                &ignored_colliders,
+            ) {
+                path_hit = true;
+                self.apply_hit(&segment_hit, end - begin, ctx);
+                segments.push((begin, segment_hit.position, true));
+
+                match self.pass_through(&segment_hit, begin, end, ctx, &mut ignored_colliders) {
+                    Some((next_begin, next_end)) => {
+                        begin = next_begin;
+                        end = next_end;
+                    }
+                    None => {
+                        stopped = true;
+                        break;
+                    }
+                }
+            }
+
+            if !stopped {
+                segments.push((begin, end, false));
+
+                // Continue movement from the end of the path.
+                if segments.len() > 1 {
+                    self.dir = (end - begin)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or(self.dir);
+                    ctx.scene.graph[ctx.handle]
+                        .local_transform_mut()
+                        .set_position(end)
+                        .set_rotation(vector_to_quat(self.dir));
+                    ctx.scene
+                        .graph
+                        .update_hierarchical_data_for_descendants(ctx.handle);
+                }
+            }
+
+            self.update_rails(&segments, ctx.scene);
+
+            self.last_position = end;
         }
 
-        if hit.is_none() {
+        if !path_hit {
+            let level = current_level_ref(ctx.plugins).unwrap();
+
             // Collect hits from self collider.
             if let Some(collider) = ctx.scene.graph.try_get_of_type::<Collider>(self.collider) {
                 let owner_character =
@@ -448,120 +1232,11 @@ impl ScriptTrait for Projectile {
         }
 
         if let Some(hit) = hit {
-            let damage = self
-                .damage
-                .scale(hit.hit_box.map_or(1.0, |h| h.damage_factor));
-
-            match damage {
-                Damage::Splash { radius, amount } => {
-                    let level = current_level_ref(ctx.plugins).unwrap();
-                    // Just find out actors which must be damaged and re-cast damage message for each.
-                    for &actor_handle in level.actors.iter() {
-                        let character = character_ref(actor_handle, &ctx.scene.graph);
-                        // TODO: Add occlusion test. This will hit actors through walls.
-                        let character_position = character.position(&ctx.scene.graph);
-                        if character_position.metric_distance(&position) <= radius {
-                            ctx.message_sender.send_global(CharacterMessage {
-                                character: actor_handle,
-                                data: CharacterMessageData::Damage {
-                                    dealer: DamageDealer {
-                                        entity: hit.shooter_actor,
-                                    },
-                                    hitbox: None,
-                                    /// TODO: Maybe collect all hitboxes?
-                                    amount,
-                                    critical_hit_probability: self.critical_hit_probability,
-                                    position: Some(DamagePosition {
-                                        point: hit.position,
-                                        direction,
-                                    }),
-                                },
-                            });
-                        }
-                    }
-                }
-                Damage::Point(amount) => {
-                    ctx.message_sender.send_global(CharacterMessage {
-                        character: hit.hit_actor,
-                        data: CharacterMessageData::Damage {
-                            dealer: DamageDealer {
-                                entity: hit.shooter_actor,
-                            },
-                            hitbox: hit.hit_box,
-                            amount,
-                            critical_hit_probability: self.critical_hit_probability,
-                            position: Some(DamagePosition {
-                                point: hit.position,
-                                direction,
-                            }),
-                        },
-                    });
-                }
-            }
-
-            if let Ok(effect_prefab) = block_on(ctx.resource_manager.request_model(
-                if hit.hit_actor.is_some() {
-                    "data/models/blood_splatter.rgs"
-                } else {
-                    "data/models/bullet_impact.rgs"
-                },
-            )) {
-                effect_prefab.instantiate_at(ctx.scene, hit.position, vector_to_quat(hit.normal));
-            }
-
-            if let Some(impact_sound) = self.impact_sound.as_ref() {
-                game.level
-                    .as_ref()
-                    .unwrap()
-                    .sound_manager
-                    .play_sound_buffer(
-                        &mut ctx.scene.graph,
-                        impact_sound,
-                        hit.position,
-                        1.0,
-                        4.0,
-                        3.0,
-                    );
-            }
-
-            Decal::new_bullet_hole(
-                ctx.resource_manager,
-                &mut ctx.scene.graph,
-                hit.position,
-                hit.normal,
-                hit.collider,
-                if hit.hit_actor.is_some() {
-                    Color::opaque(160, 0, 0)
-                } else {
-                    Color::opaque(20, 20, 20)
-                },
-            );
-
-            // Add blood splatter on a surface behind an actor that was shot.
-            if try_get_character_ref(hit.hit_actor, &ctx.scene.graph).is_some() {
-                for intersection in hit.query_buffer.iter() {
-                    if matches!(
-                        ctx.scene.graph[intersection.collider].as_collider().shape(),
-                        ColliderShape::Trimesh(_)
-                    ) && intersection.position.coords.metric_distance(&hit.position) < 2.0
-                    {
-                        Decal::spawn(
-                            &mut ctx.scene.graph,
-                            intersection.position.coords,
-                            hit.normal,
-                            Handle::NONE,
-                            Color::opaque(255, 255, 255),
-                            Vector3::new(0.45, 0.45, 0.2),
-                            ctx.resource_manager.request_texture(
-                                "data/textures/decals/BloodSplatter_BaseColor.png",
-                            ),
-                        );
-
-                        break;
-                    }
-                }
-            }
+            self.apply_hit(&hit, direction, ctx);
+            stopped = true;
+        }
 
+        if stopped {
             // Defer destruction.
             ctx.scene.graph[ctx.handle].set_lifetime(Some(0.0));
         }
@@ -1,5 +1,6 @@
 use crate::{
     character::{Character, CharacterMessage, CharacterMessageData},
+    weapon::try_weapon_ref,
     CollisionGroups,
 };
 use fyrox::{
@@ -76,6 +77,8 @@ const NORMAL_COLOR: Color = Color::from_rgba(0, 162, 232, 200);
 const NORMAL_RADIUS: f32 = 0.0012;
 const ENEMY_KILLED_TIME: f32 = 0.55;
 const HIT_DETECTED_TIME: f32 = 0.4;
+// How much the ray is dilated per one degree of spread of the weapon.
+const SPREAD_DILATION: f32 = 0.5;
 
 fn find_parent_character(sight: Handle<Node>, graph: &Graph) -> Option<(Handle<Node>, &Character)> {
     graph.find_up_map(sight, &mut |n| {
@@ -152,10 +155,17 @@ impl ScriptTrait for LaserSight {
     }
 
     fn on_update(&mut self, ctx: &mut ScriptContext) {
-        let ignore_collider = find_parent_character(ctx.handle, &ctx.scene.graph)
+        let parent_character = find_parent_character(ctx.handle, &ctx.scene.graph);
+
+        let ignore_collider = parent_character
             .map(|(_, c)| c.capsule_collider)
             .unwrap_or_default();
 
+        // Thicker ray means less accurate shots.
+        let spread_dilation = parent_character
+            .and_then(|(_, c)| try_weapon_ref(c.current_weapon(), &ctx.scene.graph))
+            .map_or(1.0, |w| 1.0 + w.spread() * SPREAD_DILATION);
+
         let this_node = &ctx.scene.graph[ctx.handle];
         let position = this_node.global_position();
         let direction = this_node.look_vector();
@@ -224,11 +234,14 @@ impl ScriptTrait for LaserSight {
                         let color = end_color.lerp(*begin_color, t);
                         let dilation_factor = lerpf(1.0, *dilation_factor, t);
                         self.set_color(&mut ctx.scene.graph, color);
-                        self.dilate(&mut ctx.scene.graph, dilation_factor);
+                        self.dilate(&mut ctx.scene.graph, dilation_factor * spread_dilation);
+                        return;
                     }
                 }
             }
         }
+
+        self.dilate(&mut ctx.scene.graph, spread_dilation);
     }
 
     fn on_message(
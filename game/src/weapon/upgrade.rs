@@ -0,0 +1,54 @@
+//! Weapon upgrades that are made on work benches.
+
+use fyrox::core::visitor::prelude::*;
+use strum_macros::AsRefStr;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, AsRefStr)]
+pub enum UpgradeStat {
+    Damage,
+    Magazine,
+    ReloadSpeed,
+    FireRate,
+}
+
+impl UpgradeStat {
+    pub const ALL: [UpgradeStat; 4] = [
+        UpgradeStat::Damage,
+        UpgradeStat::Magazine,
+        UpgradeStat::ReloadSpeed,
+        UpgradeStat::FireRate,
+    ];
+}
+
+/// Current levels of upgrades of a weapon.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Visit)]
+pub struct WeaponUpgrades {
+    pub damage: u32,
+    pub magazine: u32,
+    pub reload_speed: u32,
+    pub fire_rate: u32,
+}
+
+impl WeaponUpgrades {
+    pub fn level(&self, stat: UpgradeStat) -> u32 {
+        match stat {
+            UpgradeStat::Damage => self.damage,
+            UpgradeStat::Magazine => self.magazine,
+            UpgradeStat::ReloadSpeed => self.reload_speed,
+            UpgradeStat::FireRate => self.fire_rate,
+        }
+    }
+
+    pub fn level_mut(&mut self, stat: UpgradeStat) -> &mut u32 {
+        match stat {
+            UpgradeStat::Damage => &mut self.damage,
+            UpgradeStat::Magazine => &mut self.magazine,
+            UpgradeStat::ReloadSpeed => &mut self.reload_speed,
+            UpgradeStat::FireRate => &mut self.fire_rate,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
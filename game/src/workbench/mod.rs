@@ -0,0 +1,179 @@
+//! Work bench is a place where the player could upgrade weapons using upgrade components.
+
+use crate::{
+    current_level_mut, game_mut,
+    level::item::ItemKind,
+    player::Player,
+    weapon::{try_weapon_ref, upgrade::UpgradeStat, Weapon},
+};
+use fyrox::{
+    core::{
+        reflect::prelude::*,
+        sstorage::ImmutableString,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_component_provider,
+    material::{Material, PropertyValue, SharedMaterial},
+    resource::texture::Texture,
+    scene::{
+        graph::Graph,
+        mesh::Mesh,
+        node::{NodeHandle, TypeUuidProvider},
+    },
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+    utils::log::Log,
+};
+
+pub mod ui;
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct WorkBench {
+    #[reflect(description = "An array of handles to meshes that represents interactive screens.")]
+    screens: Vec<NodeHandle>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    selection: usize,
+}
+
+impl_component_provider!(WorkBench);
+
+impl TypeUuidProvider for WorkBench {
+    fn type_uuid() -> Uuid {
+        uuid!("bea432b3-02cb-4c4e-bdc4-f5023c4b0f49")
+    }
+}
+
+impl WorkBench {
+    pub fn selected_stat(&self) -> UpgradeStat {
+        UpgradeStat::ALL[self.selection % UpgradeStat::ALL.len()]
+    }
+
+    pub fn select_next(&mut self) {
+        self.selection = (self.selection + 1) % UpgradeStat::ALL.len();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selection = self
+            .selection
+            .checked_sub(1)
+            .unwrap_or(UpgradeStat::ALL.len() - 1);
+    }
+
+    fn apply_screen_texture(
+        &self,
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+        texture: Texture,
+    ) {
+        for &node_handle in &self.screens {
+            if let Some(mesh) = graph[*node_handle].cast_mut::<Mesh>() {
+                let mut material = Material::standard();
+
+                Log::verify(material.set_property(
+                    &ImmutableString::new("diffuseTexture"),
+                    PropertyValue::Sampler {
+                        value: Some(texture.clone()),
+                        fallback: Default::default(),
+                    },
+                ));
+
+                Log::verify(material.set_property(
+                    &ImmutableString::new("emissionTexture"),
+                    PropertyValue::Sampler {
+                        value: Some(resource_manager.request_texture("data/ui/white_pixel.bmp")),
+                        fallback: Default::default(),
+                    },
+                ));
+
+                if let Some(first_surface) = mesh.surfaces_mut().get_mut(0) {
+                    first_surface.set_material(SharedMaterial::new(material));
+                }
+            }
+        }
+    }
+}
+
+impl ScriptTrait for WorkBench {
+    fn on_init(&mut self, ctx: &mut ScriptContext) {
+        current_level_mut(ctx.plugins)
+            .expect("Level must exist!")
+            .workbenches
+            .push(ctx.handle);
+    }
+
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        let game = game_mut(ctx.plugins);
+        let texture = game
+            .workbench_ui_container
+            .create_ui(game.smaller_font.clone(), ctx.handle);
+        self.apply_screen_texture(&mut ctx.scene.graph, ctx.resource_manager.clone(), texture);
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        if let Some(level) = current_level_mut(ctx.plugins) {
+            if let Some(position) = level.workbenches.iter().position(|w| *w == ctx.node_handle) {
+                level.workbenches.remove(position);
+            }
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let game = game_mut(ctx.plugins);
+
+        let player = match game.level.as_ref().and_then(|level| {
+            ctx.scene
+                .graph
+                .try_get(level.player)
+                .and_then(|p| p.try_get_script::<Player>())
+        }) {
+            Some(player) => player,
+            None => return,
+        };
+
+        if let Some(ui) = game.workbench_ui_container.get_ui_mut(ctx.handle) {
+            if let Some(weapon) = try_weapon_ref(player.current_weapon(), &ctx.scene.graph) {
+                let definition = &Weapon::definition(weapon.kind()).upgrades;
+                let selected = self.selected_stat();
+
+                ui.set_weapon_text(weapon.kind().as_ref().to_string());
+
+                ui.set_stats_text(
+                    UpgradeStat::ALL
+                        .iter()
+                        .map(|&stat| {
+                            format!(
+                                "{} {} {}/{}",
+                                if stat == selected { ">" } else { " " },
+                                stat.as_ref(),
+                                weapon.upgrades().level(stat),
+                                definition.max_level
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+
+                ui.set_text(if weapon.can_upgrade(selected) {
+                    format!(
+                        "Cost {} ({})",
+                        definition.cost,
+                        player.inventory().item_count(ItemKind::UpgradeComponent)
+                    )
+                } else {
+                    "Maxed".to_string()
+                });
+            } else {
+                ui.set_weapon_text("No Weapon".to_string());
+                ui.set_stats_text(Default::default());
+                ui.set_text(Default::default());
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    ui_container::{InteractiveUi, UiContainer},
+    MessageDirection, UiNode, WidgetBuilder,
+};
+use fyrox::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        grid::{Column, GridBuilder, Row},
+        text::{TextBuilder, TextMessage},
+        ttf::SharedFont,
+        HorizontalAlignment, Thickness, UserInterface, VerticalAlignment,
+    },
+    resource::texture::Texture,
+    scene::node::Node,
+};
+
+pub struct WorkBenchUi {
+    pub ui: UserInterface,
+    pub render_target: Texture,
+    weapon_text: Handle<UiNode>,
+    stats_text: Handle<UiNode>,
+    text: Handle<UiNode>,
+}
+
+impl InteractiveUi for WorkBenchUi {
+    fn ui(&mut self) -> &mut UserInterface {
+        &mut self.ui
+    }
+
+    fn texture(&self) -> Texture {
+        self.render_target.clone()
+    }
+
+    fn update(&mut self, delta: f32) {
+        self.ui
+            .update(Vector2::new(Self::WIDTH, Self::HEIGHT), delta);
+
+        // Just pump all messages, but ignore them in game code.
+        while self.ui.poll_message().is_some() {}
+    }
+}
+
+impl WorkBenchUi {
+    pub const WIDTH: f32 = 200.0;
+    pub const HEIGHT: f32 = 160.0;
+
+    pub fn new(font: SharedFont) -> Self {
+        let mut ui = UserInterface::new(Vector2::new(Self::WIDTH, Self::HEIGHT));
+        let render_target = Texture::new_render_target(Self::WIDTH as u32, Self::HEIGHT as u32);
+
+        let ctx = &mut ui.build_ctx();
+
+        let weapon_text;
+        let stats_text;
+        let text;
+
+        BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(Self::WIDTH)
+                .with_height(Self::HEIGHT)
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child({
+                                weapon_text = TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(0)
+                                        .on_column(0)
+                                        .with_margin(Thickness::top(10.0)),
+                                )
+                                .with_font(font.clone())
+                                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                                .with_text("Work Bench")
+                                .build(ctx);
+                                weapon_text
+                            })
+                            .with_child({
+                                stats_text = TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(1)
+                                        .on_column(0)
+                                        .with_margin(Thickness::left(10.0)),
+                                )
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .with_font(font.clone())
+                                .build(ctx);
+                                stats_text
+                            })
+                            .with_child({
+                                text = TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(2)
+                                        .on_column(0)
+                                        .with_margin(Thickness::bottom(10.0))
+                                        .with_foreground(Brush::Solid(Color::GREEN)),
+                                )
+                                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                                .with_font(font)
+                                .build(ctx);
+                                text
+                            }),
+                    )
+                    .add_column(Column::stretch())
+                    .add_row(Row::auto())
+                    .add_row(Row::stretch())
+                    .add_row(Row::auto())
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        Self {
+            ui,
+            render_target,
+            weapon_text,
+            stats_text,
+            text,
+        }
+    }
+
+    pub fn set_weapon_text(&mut self, text: String) {
+        self.ui.send_message(TextMessage::text(
+            self.weapon_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    pub fn set_stats_text(&mut self, text: String) {
+        self.ui.send_message(TextMessage::text(
+            self.stats_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+}
+
+pub type WorkBenchUiContainer = UiContainer<Node, WorkBenchUi>;
+
+impl WorkBenchUiContainer {
+    pub fn create_ui(&mut self, font: SharedFont, work_bench_handle: Handle<Node>) -> Texture {
+        self.add(work_bench_handle, WorkBenchUi::new(font))
+    }
+}